@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use crate::Dse;
+
 pub trait Context: Sized + 'static {
     type Blackboard: Blackboard;
     type Input: Input<Self>;
@@ -8,6 +10,16 @@ pub trait Context: Sized + 'static {
     type AdditionalDseId: Hash + Eq + Copy + Debug;
     type StreamDseExtraData: Clone;
     type DseTarget: PartialEq + Clone + Debug;
+
+    /// Per-entity scaling of a DSE's initial weight, applied on top of its [DecisionWeight] and
+    /// any [WeightedDse] multiplier, e.g. from personality traits. Must be positive and finite
+    ///
+    /// [DecisionWeight]: crate::DecisionWeight
+    /// [WeightedDse]: crate::WeightedDse
+    #[allow(unused_variables)]
+    fn dse_weight_multiplier(dse: &dyn Dse<Self>, blackboard: &Self::Blackboard) -> f32 {
+        1.0
+    }
 }
 
 pub trait Input<C: Context>: Hash + Clone + Eq {
@@ -579,7 +579,15 @@ mod realisation {
             let mut considerations = Considerations::new(bump);
             let mut targets = Targets::new(bump);
             for (dse, multiplier, src) in iter_all_dses_with_sources(intelligence, &streams) {
-                let score = dse.weight().multiplier() * multiplier;
+                let entity_multiplier = C::dse_weight_multiplier(dse, blackboard);
+                debug_assert!(
+                    entity_multiplier.is_sign_positive() && entity_multiplier.is_finite(),
+                    "bad dse weight multiplier {} for {}",
+                    entity_multiplier,
+                    dse.name()
+                );
+
+                let score = dse.weight().multiplier() * multiplier * entity_multiplier;
                 dse.considerations(&mut considerations);
 
                 let realised = RealisedDse {
@@ -911,6 +919,58 @@ mod tests {
         };
     }
 
+    #[test]
+    fn entity_dse_weight_multiplier() {
+        let mut blackboard = Box::new(TestBlackboard {
+            my_hunger: 0.5,
+            ..Default::default()
+        });
+
+        #[derive(Clone, Hash, Eq, PartialEq)]
+        pub struct LazyDse;
+
+        impl Dse<TestContext> for LazyDse {
+            fn considerations(&self, out: &mut Considerations<TestContext>) {
+                out.add(ConstantConsideration(40));
+            }
+
+            fn weight(&self) -> DecisionWeight {
+                DecisionWeight::Normal
+            }
+
+            fn action(&self, _: &mut TestBlackboard, _: Option<u32>) -> TestAction {
+                TestAction::Nop
+            }
+        }
+
+        let dses = vec![
+            AiBox::new(EatDse) as AiBox<dyn Dse<TestContext>>,
+            AiBox::new(LazyDse) as AiBox<dyn Dse<TestContext>>,
+        ];
+
+        let mut intelligence = Intelligence::new(dses.into_iter());
+        let alloc = bumpalo::Bump::new();
+
+        // hungrier than lazy
+        assert!(matches!(
+            intelligence.choose(blackboard.clone(), &alloc, &()),
+            IntelligentDecision::New {
+                action: TestAction::Eat,
+                ..
+            }
+        ));
+
+        // this entity is particularly lazy
+        blackboard.dse_weights.push(("Lazy", 2.0));
+        assert!(matches!(
+            intelligence.choose(blackboard.clone(), &alloc, &()),
+            IntelligentDecision::New {
+                action: TestAction::Nop,
+                ..
+            }
+        ));
+    }
+
     #[derive(Clone, Hash, Eq, PartialEq)]
     pub struct TargetedDse;
 
@@ -975,6 +1035,7 @@ mod tests {
         let blackboard = Box::new(TestBlackboard {
             my_hunger: 0.5,
             targets: vec![100, 5],
+            ..Default::default()
         });
         let alloc = bumpalo::Bump::new();
 
@@ -1021,6 +1082,7 @@ mod tests {
         let blackboard = Box::new(TestBlackboard {
             my_hunger: 0.5,
             targets: vec![1, 2, 5],
+            ..Default::default()
         });
         let alloc = bumpalo::Bump::new();
 
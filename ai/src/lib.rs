@@ -76,6 +76,8 @@ mod test_utils {
     pub struct TestBlackboard {
         pub my_hunger: f32,
         pub targets: Vec<u32>,
+        /// (dse name, multiplier)
+        pub dse_weights: Vec<(&'static str, f32)>,
     }
 
     impl Blackboard for TestBlackboard {
@@ -95,6 +97,14 @@ mod test_utils {
         type AdditionalDseId = u32;
         type StreamDseExtraData = u32;
         type DseTarget = u32;
+
+        fn dse_weight_multiplier(dse: &dyn Dse<Self>, blackboard: &TestBlackboard) -> f32 {
+            blackboard
+                .dse_weights
+                .iter()
+                .find_map(|(name, weight)| (*name == dse.name()).then_some(*weight))
+                .unwrap_or(1.0)
+        }
     }
 
     pub struct MyHungerConsideration;
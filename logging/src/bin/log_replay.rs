@@ -0,0 +1,64 @@
+//! Pretty-prints records from a replay log, optionally only those with matching key-values,
+//! e.g. `log-replay e=E1:2` for a single entity's history
+
+use std::path::PathBuf;
+
+use logging::replay::{replay_log_path, ReplayLogReader};
+
+fn main() {
+    let mut path = replay_log_path();
+    let mut filters = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--file" {
+            path = PathBuf::from(args.next().expect("missing path after --file"));
+        } else if let Some((key, value)) = arg.split_once('=') {
+            filters.push((key.to_owned(), value.to_owned()));
+        } else {
+            eprintln!("usage: log-replay [--file PATH] [KEY=VALUE]...");
+            std::process::exit(1);
+        }
+    }
+
+    let reader = match ReplayLogReader::open(&path) {
+        Ok(reader) => reader,
+        Err(err) => {
+            eprintln!("failed to open replay log {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    };
+
+    for record in reader {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                eprintln!("failed to read record: {}", err);
+                std::process::exit(1);
+            }
+        };
+
+        if !filters
+            .iter()
+            .all(|(k, v)| record.value(k) == Some(v.as_str()))
+        {
+            continue;
+        }
+
+        let kvs = record
+            .kvs
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{} {} [{}] {}{}{}",
+            record.timestamp,
+            record.level.as_short_str(),
+            record.module,
+            record.msg,
+            if kvs.is_empty() { "" } else { " | " },
+            kvs
+        );
+    }
+}
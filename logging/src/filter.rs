@@ -0,0 +1,217 @@
+//! Per-module log level overrides that can be changed at runtime, e.g. trace for `ai` but only
+//! warnings for `world::loader`
+
+use std::sync::RwLock;
+
+use slog::{Drain, Level, OwnedKVList, Record, RecordStatic};
+
+/// Tag given to all records whose level was decided by an override, so drains after the async
+/// queue can tell without reading the overrides again (which may have changed since). Replaces
+/// any existing tag, otherwise tagged records would still be dropped by the terminal level cap
+pub const OVERRIDDEN_TAG: &str = "level-override";
+
+static OVERRIDES: RwLock<ModuleLevels> = RwLock::new(ModuleLevels(Vec::new()));
+
+/// Module path prefix -> level, most specific prefix wins
+#[derive(Default)]
+struct ModuleLevels(Vec<(String, Level)>);
+
+/// Filters records by the overridden level of their module, falling back to a default level.
+/// Overrides are read when a record is logged, so this should come before any async queue
+pub struct ModuleLevelFilter<D> {
+    drain: D,
+    default: Level,
+}
+
+/// Overrides the level of all records from the given module and its submodules, e.g. `ai` matches
+/// `ai::system` but not `aim`. Replaces any existing override for the same module
+pub fn set_module_level(module: impl Into<String>, level: Level) {
+    OVERRIDES
+        .write()
+        .expect("poisoned")
+        .set(module.into(), level)
+}
+
+/// Returns the removed override, if any
+pub fn clear_module_level(module: &str) -> Option<Level> {
+    OVERRIDES.write().expect("poisoned").clear(module)
+}
+
+pub fn clear_module_levels() {
+    OVERRIDES.write().expect("poisoned").0.clear();
+}
+
+/// Current overrides, sorted by module
+pub fn module_levels() -> Vec<(String, Level)> {
+    let mut overrides = OVERRIDES.read().expect("poisoned").0.clone();
+    overrides.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    overrides
+}
+
+impl ModuleLevels {
+    fn set(&mut self, module: String, level: Level) {
+        match self.0.iter_mut().find(|(m, _)| *m == module) {
+            Some((_, existing)) => *existing = level,
+            None => self.0.push((module, level)),
+        }
+    }
+
+    fn clear(&mut self, module: &str) -> Option<Level> {
+        let idx = self.0.iter().position(|(m, _)| m == module)?;
+        Some(self.0.swap_remove(idx).1)
+    }
+
+    fn level(&self, module: &str) -> Option<Level> {
+        self.0
+            .iter()
+            .filter(|(prefix, _)| {
+                module
+                    .strip_prefix(prefix.as_str())
+                    .map(|rest| rest.is_empty() || rest.starts_with("::"))
+                    .unwrap_or(false)
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+    }
+}
+
+impl<D: Drain> ModuleLevelFilter<D> {
+    pub fn new(drain: D, default: Level) -> Self {
+        Self { drain, default }
+    }
+}
+
+impl<D: Drain> Drain for ModuleLevelFilter<D> {
+    type Ok = Option<D::Ok>;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let overridden = OVERRIDES.read().expect("poisoned").level(record.module());
+
+        let level = overridden.unwrap_or(self.default);
+        if !record.level().is_at_least(level) {
+            return Ok(None);
+        }
+
+        if overridden.is_some() {
+            let tagged = RecordStatic {
+                location: record.location(),
+                tag: OVERRIDDEN_TAG,
+                level: record.level(),
+            };
+            let record = Record::new(&tagged, record.msg(), record.kv());
+            self.drain.log(&record, values).map(Some)
+        } else {
+            self.drain.log(record, values).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use slog::{o, Logger};
+
+    use super::*;
+
+    fn levels(overrides: &[(&str, Level)]) -> ModuleLevels {
+        let mut levels = ModuleLevels::default();
+        for (module, level) in overrides {
+            levels.set(module.to_string(), *level);
+        }
+        levels
+    }
+
+    #[test]
+    fn prefix_matching() {
+        let levels = levels(&[("ai", Level::Trace), ("world", Level::Warning)]);
+
+        assert_eq!(levels.level("ai"), Some(Level::Trace));
+        assert_eq!(levels.level("ai::system"), Some(Level::Trace));
+        assert_eq!(levels.level("aim"), None);
+        assert_eq!(levels.level("aim::ai"), None);
+        assert_eq!(levels.level("simulation::ai"), None);
+        assert_eq!(levels.level("world::loader"), Some(Level::Warning));
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let levels = levels(&[
+            ("world::loader::worker", Level::Trace),
+            ("world", Level::Warning),
+            ("world::loader", Level::Error),
+        ]);
+
+        assert_eq!(levels.level("world::chunk"), Some(Level::Warning));
+        assert_eq!(levels.level("world::loader"), Some(Level::Error));
+        assert_eq!(levels.level("world::loader::batch"), Some(Level::Error));
+        assert_eq!(
+            levels.level("world::loader::worker::thread"),
+            Some(Level::Trace)
+        );
+    }
+
+    #[test]
+    fn set_and_clear() {
+        let mut levels = levels(&[("ai", Level::Trace), ("world", Level::Warning)]);
+
+        levels.set("ai".to_owned(), Level::Info);
+        assert_eq!(levels.0.len(), 2);
+        assert_eq!(levels.level("ai::system"), Some(Level::Info));
+
+        assert_eq!(levels.clear("ai"), Some(Level::Info));
+        assert_eq!(levels.clear("ai"), None);
+        assert_eq!(levels.level("ai::system"), None);
+        assert_eq!(levels.level("world"), Some(Level::Warning));
+    }
+
+    #[derive(Default)]
+    struct Collect(Mutex<Vec<(Level, String, String)>>);
+
+    impl Drain for &'static Collect {
+        type Ok = ();
+        type Err = slog::Never;
+
+        fn log(&self, record: &Record, _: &OwnedKVList) -> Result<(), slog::Never> {
+            self.0.lock().unwrap().push((
+                record.level(),
+                record.tag().to_owned(),
+                record.msg().to_string(),
+            ));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn overridden_records_are_tagged() {
+        let collected: &'static Collect = Box::leak(Box::default());
+        let logger = Logger::root(ModuleLevelFilter::new(collected, Level::Info).fuse(), o!());
+
+        slog::trace!(logger, "dropped");
+        slog::info!(logger, "default");
+
+        // unique to this test, as overrides are global
+        set_module_level(module_path!(), Level::Trace);
+        slog::trace!(logger, "overridden");
+        slog::trace!(logger, #"custom", "custom tag");
+        clear_module_level(module_path!());
+
+        assert_eq!(
+            *collected.0.lock().unwrap(),
+            vec![
+                (Level::Info, String::new(), "default".to_owned()),
+                (
+                    Level::Trace,
+                    OVERRIDDEN_TAG.to_owned(),
+                    "overridden".to_owned()
+                ),
+                (
+                    Level::Trace,
+                    OVERRIDDEN_TAG.to_owned(),
+                    "custom tag".to_owned()
+                ),
+            ]
+        );
+    }
+}
@@ -5,6 +5,8 @@ use slog::{Drain, Level};
 use slog_scope::GlobalLoggerGuard;
 use slog_term::ThreadSafeTimestampFn;
 
+use crate::filter::{ModuleLevelFilter, OVERRIDDEN_TAG};
+
 pub struct LoggerBuilder {
     level: Level,
 }
@@ -40,10 +42,16 @@ impl LoggerBuilder {
                 .stderr()
                 .force_color()
                 .build();
-            slog_term::CompactFormat::new(decorator)
+            let drain = slog_term::CompactFormat::new(decorator)
                 .use_custom_timestamp(timestamp_fn)
-                .build()
-                .filter_level(self.level.min(Level::Debug)) // dont spam terminal with trace
+                .build();
+
+            // dont spam terminal with trace, unless explicitly overridden for a module
+            let level = self.level.min(Level::Debug);
+            drain
+                .filter(move |record| {
+                    record.level().is_at_least(level) || record.tag() == OVERRIDDEN_TAG
+                })
                 .fuse()
         };
 
@@ -81,17 +89,35 @@ impl LoggerBuilder {
             #[cfg(not(feature = "to-file"))]
             terminal_drain
         };
+
+        #[cfg(feature = "replay-log")]
+        let drain = {
+            let log_file = crate::replay::replay_log_path();
+            if log_file.is_file() {
+                // try to backup
+                let mut bak = log_file.clone();
+                bak.set_extension("bak");
+                let _ = std::fs::rename(&log_file, &bak);
+            }
+
+            let replay_drain = crate::replay::ReplayLogDrain::create(&log_file, timestamp_fn)
+                .map_err(LogError::Io)?;
+            slog::Duplicate::new(drain, replay_drain.ignore_res())
+        };
+
         let chan_size = match self.level {
             Level::Debug | Level::Trace => 0x20000,
             _ => 0x4000,
         };
 
-        let drain = drain.filter_level(self.level).fuse();
-        let drain = slog_async::Async::new(drain)
+        let drain = slog_async::Async::new(drain.fuse())
             .thread_name("logging".to_owned())
             .chan_size(chan_size)
-            .build_no_guard()
-            .fuse();
+            .build_no_guard();
+
+        // filter before queueing so overrides apply to records logged after they are changed, and
+        // the terminal filter only needs to check the tag
+        let drain = ModuleLevelFilter::new(drain, self.level).fuse();
         let logger = slog::Logger::root(drain, slog::o!());
 
         let global = slog_scope::set_global_logger(logger);
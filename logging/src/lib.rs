@@ -4,6 +4,15 @@ mod init;
 #[cfg(feature = "binary")]
 pub use init::LoggerBuilder;
 
+#[cfg(feature = "replay-log")]
+pub mod replay;
+
+mod filter;
+pub use filter::{
+    clear_module_level, clear_module_levels, module_levels, set_module_level, ModuleLevelFilter,
+    OVERRIDDEN_TAG,
+};
+
 // can't be cfg(test) because this is used as a dependency in tested crates, and so isn't compiled
 // with cfg(test)
 mod tests;
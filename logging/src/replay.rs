@@ -0,0 +1,268 @@
+//! Compact binary log of all records and their key-values, for replaying an entity's history
+//! after the fact with the `log-replay` tool
+
+use std::fmt::Arguments;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use slog::{Drain, Key, Level, OwnedKVList, Record, Serializer, KV};
+use slog_term::ThreadSafeTimestampFn;
+
+const MAGIC: &[u8; 4] = b"NNRL";
+const VERSION: u8 = 1;
+
+/// Default location of the replay log
+pub fn replay_log_path() -> PathBuf {
+    std::env::temp_dir().join("nn-replay-log")
+}
+
+pub struct ReplayLogDrain<T> {
+    file: Mutex<File>,
+    timestamp_fn: T,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayRecord {
+    pub level: Level,
+    /// As formatted by the logger's timestamp function
+    pub timestamp: String,
+    pub module: String,
+    pub msg: String,
+    /// Record key-values followed by those of the logger (e.g. from `log_scope!`)
+    pub kvs: Vec<(String, String)>,
+}
+
+pub struct ReplayLogReader<R> {
+    reader: R,
+}
+
+#[derive(Default)]
+struct KvCollector(Vec<(String, String)>);
+
+impl<T: ThreadSafeTimestampFn> ReplayLogDrain<T> {
+    pub fn create(path: &Path, timestamp_fn: T) -> std::io::Result<Self> {
+        let mut file = File::create(path)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+        Ok(Self {
+            file: Mutex::new(file),
+            timestamp_fn,
+        })
+    }
+}
+
+impl<T: ThreadSafeTimestampFn> Drain for ReplayLogDrain<T> {
+    type Ok = ();
+    type Err = std::io::Error;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let mut timestamp = Vec::new();
+        (self.timestamp_fn)(&mut timestamp)?;
+
+        let mut kvs = KvCollector::default();
+        record
+            .kv()
+            .serialize(record, &mut kvs)
+            .and_then(|_| values.serialize(record, &mut kvs))
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+        // encode whole record first for a single write
+        let mut buf = Vec::with_capacity(128);
+        buf.push(record.level().as_usize() as u8);
+        write_bytes(&mut buf, &timestamp);
+        write_bytes(&mut buf, record.module().as_bytes());
+        write_bytes(&mut buf, record.msg().to_string().as_bytes());
+        buf.extend_from_slice(&(kvs.0.len() as u32).to_le_bytes());
+        for (k, v) in &kvs.0 {
+            write_bytes(&mut buf, k.as_bytes());
+            write_bytes(&mut buf, v.as_bytes());
+        }
+
+        let mut file = self.file.lock().expect("poisoned");
+        file.write_all(&buf)
+    }
+}
+
+impl ReplayLogReader<BufReader<File>> {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> ReplayLogReader<R> {
+    pub fn new(mut reader: R) -> std::io::Result<Self> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                "not a replay log",
+            ));
+        }
+
+        if header[4] != VERSION {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported replay log version {}", header[4]),
+            ));
+        }
+
+        Ok(Self { reader })
+    }
+
+    fn read_record(&mut self) -> std::io::Result<Option<ReplayRecord>> {
+        let mut level = [0u8];
+        match self.reader.read_exact(&mut level) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            res => res?,
+        }
+
+        let level = Level::from_usize(level[0] as usize)
+            .ok_or_else(|| std::io::Error::new(ErrorKind::InvalidData, "bad level"))?;
+        let timestamp = self.read_string()?;
+        let module = self.read_string()?;
+        let msg = self.read_string()?;
+
+        // count is untrusted, so don't preallocate
+        let kv_count = self.read_u32()?;
+        let mut kvs = Vec::new();
+        for _ in 0..kv_count {
+            kvs.push((self.read_string()?, self.read_string()?));
+        }
+
+        Ok(Some(ReplayRecord {
+            level,
+            timestamp,
+            module,
+            msg,
+            kvs,
+        }))
+    }
+
+    fn read_u32(&mut self) -> std::io::Result<u32> {
+        let mut bytes = [0u8; 4];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_string(&mut self) -> std::io::Result<String> {
+        // length is untrusted, so only allocate as much as is actually read
+        let len = self.read_u32()? as usize;
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "truncated replay log",
+            ));
+        }
+
+        String::from_utf8(bytes).map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+impl<R: Read> Iterator for ReplayLogReader<R> {
+    type Item = std::io::Result<ReplayRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+impl ReplayRecord {
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.kvs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl Serializer for KvCollector {
+    fn emit_arguments(&mut self, key: Key, val: &Arguments) -> slog::Result {
+        self.0.push((key.to_string(), val.to_string()));
+        Ok(())
+    }
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use slog::{o, Drain, Level, Logger};
+
+    use super::*;
+
+    fn temp_log(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("nn-replay-test-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_log("round-trip");
+        {
+            let drain = ReplayLogDrain::create(&path, |w: &mut dyn Write| write!(w, "T42"))
+                .expect("failed to create replay log");
+            let logger = Logger::root(drain.fuse(), o!("e" => "E1:2"));
+            slog::info!(logger, "decided"; "dse" => "Eat", "score" => 0.5);
+            slog::warn!(logger.new(o!("task" => "haul")), "interrupted");
+        }
+
+        let records = ReplayLogReader::open(&path)
+            .expect("failed to open replay log")
+            .collect::<std::io::Result<Vec<_>>>()
+            .expect("failed to read records");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(records.len(), 2);
+
+        let decided = &records[0];
+        assert_eq!(decided.level, Level::Info);
+        assert_eq!(decided.timestamp, "T42");
+        assert_eq!(decided.module, module_path!());
+        assert_eq!(decided.msg, "decided");
+        assert_eq!(decided.value("dse"), Some("Eat"));
+        assert_eq!(decided.value("score"), Some("0.5"));
+        assert_eq!(decided.value("e"), Some("E1:2"));
+
+        let interrupted = &records[1];
+        assert_eq!(interrupted.level, Level::Warning);
+        assert_eq!(interrupted.msg, "interrupted");
+        assert_eq!(interrupted.value("task"), Some("haul"));
+        assert_eq!(interrupted.value("e"), Some("E1:2"));
+    }
+
+    #[test]
+    fn corrupt_lengths() {
+        let mut log = MAGIC.to_vec();
+        log.push(VERSION);
+        log.push(Level::Info.as_usize() as u8);
+        log.extend_from_slice(&u32::MAX.to_le_bytes()); // timestamp length
+        log.extend_from_slice(b"T1");
+
+        let mut reader = ReplayLogReader::new(Cursor::new(log)).unwrap();
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        let mut log = MAGIC.to_vec();
+        log.push(VERSION);
+        log.push(Level::Info.as_usize() as u8);
+        for string in [&b"T1"[..], b"module", b"msg"] {
+            write_bytes(&mut log, string);
+        }
+        log.extend_from_slice(&u32::MAX.to_le_bytes()); // kv count
+
+        let mut reader = ReplayLogReader::new(Cursor::new(log)).unwrap();
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
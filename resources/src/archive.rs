@@ -0,0 +1,232 @@
+use crate::error::{ResourceError, ResourceErrorKind};
+use memmap::Mmap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use zip::ZipArchive;
+
+/// A zip of the contents of the resources directory, memory mapped. Files on disk take precedence
+/// over files in the archive, so loose files can override a release pak during development
+#[derive(Clone)]
+pub struct ResourceArchive {
+    path: Arc<Path>,
+    zip: ZipArchive<Cursor<SharedMmap>>,
+    /// Names of all files (not directories), relative to the resources root
+    files: Arc<HashSet<String>>,
+}
+
+#[derive(Clone)]
+struct SharedMmap(Arc<Mmap>);
+
+impl ResourceArchive {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ResourceError> {
+        let path = path.as_ref();
+        let io_err = |e| ResourceError(path.to_owned(), ResourceErrorKind::Io(Arc::new(e)));
+
+        let file = File::open(path).map_err(io_err)?;
+        let mapped = unsafe { Mmap::map(&file) }.map_err(io_err)?;
+        let zip = ZipArchive::new(Cursor::new(SharedMmap(Arc::new(mapped))))
+            .map_err(|e| ResourceError(path.to_owned(), ResourceErrorKind::Archive(Arc::new(e))))?;
+
+        let files = zip
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(str::to_owned)
+            .collect();
+
+        Ok(Self {
+            path: path.into(),
+            zip,
+            files: Arc::new(files),
+        })
+    }
+
+    /// Path of the archive on disk
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resource path is relative to the resources root
+    pub(crate) fn contains(&self, resource: &Path) -> bool {
+        entry_name(resource)
+            .map(|name| self.files.contains(&name))
+            .unwrap_or(false)
+    }
+
+    /// Whether the archive has any files within the given directory, relative to the resources root
+    pub(crate) fn contains_dir(&self, dir: &Path) -> bool {
+        self.files_in(dir).next().is_some()
+    }
+
+    /// All files recursively within the given directory, relative to the resources root
+    pub(crate) fn files_in(&self, dir: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+        let prefix = entry_name(dir).map(|mut dir| {
+            if !dir.is_empty() {
+                dir.push('/');
+            }
+            dir
+        });
+
+        self.files
+            .iter()
+            .filter(move |name| prefix.as_deref().is_some_and(|dir| name.starts_with(dir)))
+            .map(PathBuf::from)
+    }
+
+    pub(crate) fn read(&self, resource: &Path) -> Result<Vec<u8>, ResourceError> {
+        let err = |kind| ResourceError(self.path.join(resource), kind);
+        let name = entry_name(resource).ok_or_else(|| err(ResourceErrorKind::InvalidPath))?;
+
+        // cloning is cheap, the central directory is shared
+        let mut zip = self.zip.clone();
+        let mut file = zip
+            .by_name(&name)
+            .map_err(|e| err(ResourceErrorKind::Archive(Arc::new(e))))?;
+
+        let mut bytes = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut bytes)
+            .map_err(|e| err(ResourceErrorKind::Io(Arc::new(e))))?;
+        Ok(bytes)
+    }
+}
+
+/// Zip entries always use forward slashes. None if the path is not a plain relative path
+fn entry_name(resource: &Path) -> Option<String> {
+    let mut name = String::new();
+    for component in resource.components() {
+        match component {
+            Component::Normal(c) => {
+                if !name.is_empty() {
+                    name.push('/');
+                }
+                name.push_str(c.to_str()?);
+            }
+            Component::CurDir => continue,
+            _ => return None,
+        }
+    }
+
+    Some(name)
+}
+
+impl AsRef<[u8]> for SharedMmap {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    use crate::{recurse, ReadResource, ResourceArchive, ResourceContainer, Resources};
+
+    /// Creates a game dir with the given loose resources and a zip of the given archived ones.
+    /// Archived names ending in / are directory entries
+    fn setup(name: &str, disk: &[(&str, &str)], zipped: &[(&str, &str)]) -> Resources {
+        let game_dir =
+            std::env::temp_dir().join(format!("nn-archive-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&game_dir);
+        std::fs::create_dir_all(&game_dir).unwrap();
+
+        for (file, contents) in disk {
+            let path = game_dir.join("resources").join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        let archive_path = game_dir.join("resources.zip");
+        let mut zip = ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+        for (file, contents) in zipped {
+            if file.ends_with('/') {
+                zip.add_directory(*file, FileOptions::default()).unwrap();
+            } else {
+                zip.start_file(*file, FileOptions::default()).unwrap();
+                zip.write_all(contents.as_bytes()).unwrap();
+            }
+        }
+        zip.finish().unwrap();
+
+        let archive = ResourceArchive::open(&archive_path).unwrap();
+        Resources::with_archive(game_dir, archive)
+    }
+
+    fn read(container: &impl ResourceContainer, file: &str) -> String {
+        String::read_resource(container.get_file(file).unwrap()).unwrap()
+    }
+
+    fn read_all(container: &impl ResourceContainer, ext: &'static str) -> Vec<String> {
+        let mut all = recurse::<_, String>(container, ext)
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>();
+        all.sort();
+        all
+    }
+
+    #[test]
+    fn disk_overrides_archive() {
+        let resources = setup(
+            "override",
+            &[("definitions/a.ron", "disk a")],
+            &[
+                ("definitions/a.ron", "zip a"),
+                ("definitions/b.ron", "zip b"),
+            ],
+        );
+
+        let definitions = resources.definitions().unwrap();
+        assert_eq!(read(&definitions, "a.ron"), "disk a");
+        assert_eq!(read(&definitions, "b.ron"), "zip b");
+        assert!(definitions.get_file("c.ron").is_err());
+    }
+
+    #[test]
+    fn archive_only_child_dir() {
+        let resources = setup(
+            "child",
+            &[],
+            &[("definitions/a.ron", "zip a"), ("shaders/", "")],
+        );
+
+        // resources dir doesn't exist on disk at all
+        assert!(!resources.path().exists());
+
+        let definitions = resources.definitions().unwrap();
+        assert_eq!(read(&definitions, "a.ron"), "zip a");
+
+        // directory entries alone don't count
+        assert!(resources.shaders().is_err());
+        assert!(resources.fonts().is_err());
+    }
+
+    #[test]
+    fn recurse_merges_disk_and_archive() {
+        let resources = setup(
+            "recurse",
+            &[
+                ("definitions/a.ron", "disk a"),
+                ("definitions/nested/c.ron", "disk c"),
+                ("definitions/ignored.txt", "disk txt"),
+            ],
+            &[
+                ("definitions/a.ron", "zip a"),
+                ("definitions/b.ron", "zip b"),
+                ("definitions/nested/d.ron", "zip d"),
+                ("definitions/ignored.txt", "zip txt"),
+                ("shaders/e.ron", "zip e"),
+            ],
+        );
+
+        let definitions = resources.definitions().unwrap();
+        assert_eq!(
+            read_all(&definitions, "ron"),
+            vec!["disk a", "disk c", "zip b", "zip d"]
+        );
+        assert_eq!(read_all(&definitions, "txt"), vec!["disk txt"]);
+    }
+}
@@ -1,6 +1,9 @@
+#[cfg(feature = "archive")]
+use crate::archive::ResourceArchive;
 use crate::error::{ResourceError, ResourceErrorKind};
 use memmap::Mmap;
 use misc::*;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fmt::{Debug, Display, Formatter};
 use std::fs::File;
@@ -16,14 +19,39 @@ pub trait ResourceContainer {
     fn path(&self) -> &Path;
     fn component_offset(&self) -> usize;
 
+    /// Archive to fall back to for files not on disk
+    #[cfg(feature = "archive")]
+    fn archive(&self) -> Option<&ResourceArchive> {
+        None
+    }
+
+    /// Files that are not on disk, which take precedence over files on disk
+    fn in_memory_files(&self) -> Vec<ResourcePath> {
+        Vec::new()
+    }
+
     fn get_file(&self, file: impl AsRef<ResourceFile>) -> Result<ResourcePath, ResourceError> {
-        let file = self.path().join(&file.as_ref().0);
-        if !file.exists() {
-            Err(ResourceError(file, ResourceErrorKind::FileNotFound))
-        } else if !file.is_file() {
-            Err(ResourceError(file, ResourceErrorKind::NotAFile))
+        let path =
+            ResourcePath::on_disk(self.path().join(&file.as_ref().0), self.component_offset());
+        if !path.0.exists() {
+            #[cfg(feature = "archive")]
+            {
+                if let Some(archive) = self.archive() {
+                    if archive.contains(&path.resource_path()) {
+                        return Ok(ResourcePath(
+                            path.0,
+                            path.1,
+                            ResourceSource::Archive(archive.clone()),
+                        ));
+                    }
+                }
+            }
+
+            Err(ResourceError(path.0, ResourceErrorKind::FileNotFound))
+        } else if !path.0.is_file() {
+            Err(ResourceError(path.0, ResourceErrorKind::NotAFile))
         } else {
-            Ok(ResourcePath(file, self.component_offset()))
+            Ok(path)
         }
     }
 }
@@ -35,17 +63,44 @@ pub trait ReadResource: Sized {
 
 /// A path to a resource, relative to the root resource path. Not identical to a file path (e.g. no
 /// relative ../, no C:\\)
-pub struct ResourcePath(PathBuf, usize);
+pub struct ResourcePath(PathBuf, usize, ResourceSource);
+
+enum ResourceSource {
+    Disk,
+    #[cfg(feature = "archive")]
+    Archive(ResourceArchive),
+    Memory(Arc<[u8]>),
+}
 
 /// A resource file name
 #[repr(transparent)]
 pub struct ResourceFile(OsStr);
 
+/// Archive field of containers declared with [resources], present whether or not the archive
+/// feature is enabled so the macro doesn't depend on the invoking crate's features
+#[doc(hidden)]
+#[cfg(feature = "archive")]
+pub type ContainerArchive = Option<ResourceArchive>;
+
+#[doc(hidden)]
+#[cfg(not(feature = "archive"))]
+pub type ContainerArchive = Option<std::convert::Infallible>;
+
 impl ResourcePath {
+    pub(crate) fn on_disk(path: PathBuf, component_offset: usize) -> Self {
+        Self(path, component_offset, ResourceSource::Disk)
+    }
+
+    pub(crate) fn in_memory(path: PathBuf, component_offset: usize, contents: Arc<[u8]>) -> Self {
+        Self(path, component_offset, ResourceSource::Memory(contents))
+    }
+
     /// Path on disk, returns None if in-memory only
     pub fn file_path(&self) -> Option<&Path> {
-        // TODO depends on feature gate
-        Some(self.0.as_path())
+        match self.2 {
+            ResourceSource::Disk => Some(self.0.as_path()),
+            _ => None,
+        }
     }
 
     pub fn resource_path(&self) -> PathBuf {
@@ -57,6 +112,12 @@ impl ResourcePath {
     }
 }
 
+impl ResourceFile {
+    pub(crate) fn as_path(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
 impl AsRef<ResourcePath> for ResourcePath {
     fn as_ref(&self) -> &ResourcePath {
         self
@@ -90,8 +151,19 @@ impl Debug for ResourceFile {
 
 impl Display for ResourcePath {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        // TODO add feature gate info e.g. from disk, from archive
-        write!(f, "{}", self.0.display())
+        match &self.2 {
+            ResourceSource::Disk => write!(f, "{}", self.0.display()),
+            #[cfg(feature = "archive")]
+            ResourceSource::Archive(archive) => write!(
+                f,
+                "{} (in {})",
+                self.resource_path().display(),
+                archive.path().display()
+            ),
+            ResourceSource::Memory(_) => {
+                write!(f, "{} (in memory)", self.resource_path().display())
+            }
+        }
     }
 }
 
@@ -99,10 +171,70 @@ pub fn recurse<R: ResourceContainer, T: ReadResource>(
     container: &R,
     ext: &'static str,
 ) -> impl Iterator<Item = Result<T, ResourceError>> {
+    recurse_paths(container, ext).map(T::read_resource)
+}
+
+/// Reads all resources with the given extension from each layer, e.g. a directory of the base
+/// resources followed by the same directory of each mod in load order (see
+/// [layers](crate::Mods::layers)). A resource in more than one layer is only read from the last,
+/// so later layers override earlier ones
+pub fn recurse_layered<R: ResourceContainer, T: ReadResource>(
+    layers: &[R],
+    ext: &'static str,
+) -> impl Iterator<Item = Result<T, ResourceError>> {
+    let mut seen = HashSet::new();
+    let paths = layers
+        .iter()
+        .rev()
+        .flat_map(|layer| recurse_paths(layer, ext))
+        .filter(|path| seen.insert(path.resource_path()))
+        .collect_vec();
+
+    paths.into_iter().map(T::read_resource)
+}
+
+/// Gets the file from the last layer that has it, see [recurse_layered]. If none do, the error is
+/// from the first layer
+pub fn get_layered_file<R: ResourceContainer>(
+    layers: &[R],
+    file: impl AsRef<ResourceFile>,
+) -> Result<ResourcePath, ResourceError> {
+    let file = file.as_ref();
+    let mut err = None;
+    for layer in layers.iter().rev() {
+        match layer.get_file(file) {
+            Ok(path) => return Ok(path),
+            Err(e) => err = Some(e),
+        }
+    }
+
+    Err(err.unwrap_or_else(|| {
+        ResourceError(file.as_path().to_owned(), ResourceErrorKind::FileNotFound)
+    }))
+}
+
+fn recurse_paths<R: ResourceContainer>(
+    container: &R,
+    ext: &'static str,
+) -> impl Iterator<Item = ResourcePath> {
     let ext = OsStr::new(ext);
     let offset = container.component_offset();
-    WalkDir::new(container.path())
-        .into_iter()
+
+    let mut in_memory = container.in_memory_files();
+    in_memory.retain(|path| path.0.extension() == Some(ext));
+    let overridden = in_memory.iter().map(|path| path.0.clone()).collect_vec();
+
+    #[cfg(feature = "archive")]
+    let archived = archived_files(container, ext);
+    #[cfg(not(feature = "archive"))]
+    let archived = Vec::new();
+
+    // directory may only exist in an archive
+    let dir = container.path();
+    let walk = dir.is_dir().then(|| WalkDir::new(dir));
+
+    walk.into_iter()
+        .flatten()
         .filter_map(move |e| match e {
             Err(e) => {
                 warn!("failed to read resource file"; "error" => %e);
@@ -115,18 +247,49 @@ pub fn recurse<R: ResourceContainer, T: ReadResource>(
                         .map(|this_ext| this_ext == ext)
                         .unwrap_or(false)
                 {
-                    Some(ResourcePath(e.into_path(), offset))
+                    Some(ResourcePath::on_disk(e.into_path(), offset))
                 } else {
                     None
                 }
             }
         })
-        .map(T::read_resource)
+        .chain(archived)
+        .filter(move |path| !overridden.contains(&path.0))
+        .chain(in_memory)
+}
+
+/// Files in the archive that are not overridden on disk
+#[cfg(feature = "archive")]
+fn archived_files<R: ResourceContainer>(container: &R, ext: &OsStr) -> Vec<ResourcePath> {
+    let archive = match container.archive() {
+        Some(archive) => archive,
+        None => return Vec::new(),
+    };
+
+    let offset = container.component_offset();
+    let root: PathBuf = container.path().components().take(offset).collect();
+    let dir = ResourcePath::on_disk(container.path().to_owned(), offset).resource_path();
+
+    archive
+        .files_in(&dir)
+        .filter(|file| file.extension() == Some(ext))
+        .map(|file| root.join(file))
+        .filter(|path| !path.exists())
+        .map(|path| ResourcePath(path, offset, ResourceSource::Archive(archive.clone())))
+        .collect()
 }
 
 impl ReadResource for (File, Mmap, Rc<Path>) {
     fn read_resource(path: impl AsRef<ResourcePath>) -> Result<Self, ResourceError> {
         let path = path.as_ref();
+
+        if path.file_path().is_none() {
+            return Err(ResourceError(
+                path.0.to_path_buf(),
+                ResourceErrorKind::NotOnDisk,
+            ));
+        }
+
         let file = File::open(&path.0);
         file.and_then(|f| {
             let mapped = unsafe { Mmap::map(&f) };
@@ -139,6 +302,16 @@ impl ReadResource for (File, Mmap, Rc<Path>) {
 impl ReadResource for String {
     fn read_resource(path: impl AsRef<ResourcePath>) -> Result<Self, ResourceError> {
         let path = path.as_ref();
+
+        if path.file_path().is_none() {
+            return Vec::<u8>::read_resource(path).and_then(|bytes| {
+                String::from_utf8(bytes).map_err(|e| {
+                    let e = std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+                    ResourceError(path.0.to_path_buf(), ResourceErrorKind::Io(Arc::new(e)))
+                })
+            });
+        }
+
         std::fs::read_to_string(&path.0)
             .map_err(|e| ResourceError(path.0.to_path_buf(), ResourceErrorKind::Io(Arc::new(e))))
     }
@@ -147,8 +320,15 @@ impl ReadResource for String {
 impl ReadResource for Vec<u8> {
     fn read_resource(path: impl AsRef<ResourcePath>) -> Result<Self, ResourceError> {
         let path = path.as_ref();
-        std::fs::read(&path.0)
-            .map_err(|e| ResourceError(path.0.to_path_buf(), ResourceErrorKind::Io(Arc::new(e))))
+
+        match &path.2 {
+            ResourceSource::Disk => std::fs::read(&path.0).map_err(|e| {
+                ResourceError(path.0.to_path_buf(), ResourceErrorKind::Io(Arc::new(e)))
+            }),
+            #[cfg(feature = "archive")]
+            ResourceSource::Archive(archive) => archive.read(&path.resource_path()),
+            ResourceSource::Memory(contents) => Ok(contents.to_vec()),
+        }
     }
 }
 
@@ -160,6 +340,8 @@ macro_rules! resources {
         pub struct $name {
             path: PathBuf,
             component_offset: usize,
+            #[allow(dead_code)] // only read with the archive feature
+            archive: $crate::ContainerArchive,
         }
 
         impl ResourceContainer for $name {
@@ -174,19 +356,54 @@ macro_rules! resources {
             fn component_offset(&self) -> usize {
                 self.component_offset
             }
+
+            $crate::impl_container_archive!();
         }
     };
 }
 
+/// Implements [ResourceContainer::archive] for containers declared with [resources], if the
+/// archive feature is enabled for this crate
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "archive")]
+macro_rules! impl_container_archive {
+    () => {
+        #[inline]
+        fn archive(&self) -> Option<&$crate::ResourceArchive> {
+            self.archive.as_ref()
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "archive"))]
+macro_rules! impl_container_archive {
+    () => {};
+}
+
 /// Declares a pre-declared directory to be a child of this one (this = the impl block this is in)
 #[macro_export]
 macro_rules! child {
     ($name:ident, $child:ident) => {
         pub fn $name(&self) -> Result<$child, ResourceError> {
-            let path = get_dir(&self.path, $child::DIR)?;
+            let path = get_dir(&self.path, $child::DIR);
+            #[cfg(feature = "archive")]
+            let path = path.or_else(|err| {
+                get_archived_dir(
+                    &self.path,
+                    self.component_offset,
+                    &self.archive,
+                    $child::DIR,
+                )
+                .ok_or(err)
+            });
+            let path = path?;
             Ok($child {
                 path,
                 component_offset: self.component_offset,
+                archive: self.archive.clone(),
             })
         }
     };
@@ -23,4 +23,11 @@ pub enum ResourceErrorKind {
 
     #[error("Failed to read resource: {0}")]
     Io(#[source] Arc<std::io::Error>), // Arc for cloning...
+
+    #[cfg(feature = "archive")]
+    #[error("Failed to read archive: {0}")]
+    Archive(#[source] Arc<zip::result::ZipError>),
+
+    #[error("Resource is not on disk")]
+    NotOnDisk,
 }
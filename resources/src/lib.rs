@@ -1,9 +1,26 @@
+#[cfg(feature = "archive")]
+mod archive;
 mod container;
 mod error;
+mod loader;
+mod manifest;
+mod overlay;
 mod resource;
+mod watcher;
 
 pub use memmap::Mmap;
 
-pub use container::{recurse, ReadResource, ResourceContainer, ResourceFile, ResourcePath};
+#[cfg(feature = "archive")]
+pub use archive::ResourceArchive;
+pub use container::{
+    get_layered_file, recurse, recurse_layered, ContainerArchive, ReadResource, ResourceContainer,
+    ResourceFile, ResourcePath,
+};
 pub use error::{ResourceError, ResourceErrorKind};
+pub use loader::{LoadHandle, ResourceLoader};
+pub use manifest::{
+    HashedResource, ManifestEntry, ManifestMismatch, ResourceManifest, ResourceOrigin,
+};
+pub use overlay::ResourceOverlay;
 pub use resource::*;
+pub use watcher::{ResourceChange, ResourceChangeKind, ResourceWatcher};
@@ -0,0 +1,231 @@
+use crate::container::{ReadResource, ResourcePath};
+use crate::error::ResourceError;
+use misc::parking_lot::Mutex;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Reads resources on background threads. Loaded resources only become ready when passed through
+/// [ResourceLoader::pump], so the number finished per frame can be limited
+pub struct ResourceLoader {
+    /// None when dropping
+    requests: Option<Sender<Request>>,
+    completions: Receiver<Completion>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// A requested resource, shared with the loader until it's finished
+pub struct LoadHandle<T>(Arc<Mutex<LoadState<T>>>);
+
+enum LoadState<T> {
+    Loading,
+    Loaded(Result<T, ResourceError>),
+    Taken,
+}
+
+type Completion = Box<dyn FnOnce() + Send>;
+type Request = Box<dyn FnOnce() -> Completion + Send>;
+
+impl ResourceLoader {
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "no loader threads");
+
+        let (requests_tx, requests_rx) = channel::<Request>();
+        let (completions_tx, completions_rx) = channel();
+        let requests_rx = Arc::new(Mutex::new(requests_rx));
+
+        let workers = (0..threads)
+            .map(|i| {
+                let requests = requests_rx.clone();
+                let completions = completions_tx.clone();
+                std::thread::Builder::new()
+                    .name(format!("resources-{}", i))
+                    .spawn(move || loop {
+                        // lock is released before loading
+                        let request = requests.lock().recv();
+                        let request = match request {
+                            Ok(req) => req,
+                            Err(_) => break, // loader dropped
+                        };
+
+                        if completions.send(request()).is_err() {
+                            break;
+                        }
+                    })
+                    .expect("failed to spawn resource loader thread")
+            })
+            .collect();
+
+        Self {
+            requests: Some(requests_tx),
+            completions: completions_rx,
+            workers,
+        }
+    }
+
+    pub fn request<T: ReadResource + Send + 'static>(&self, path: ResourcePath) -> LoadHandle<T> {
+        let handle = LoadHandle(Arc::new(Mutex::new(LoadState::Loading)));
+
+        let state = handle.0.clone();
+        let request: Request = Box::new(move || {
+            let result = T::read_resource(&path);
+            Box::new(move || *state.lock() = LoadState::Loaded(result))
+        });
+
+        self.requests
+            .as_ref()
+            .expect("loader is running")
+            .send(request)
+            .expect("resource loader threads died");
+
+        handle
+    }
+
+    /// Finishes at most `max` loaded resources, returning how many were finished
+    pub fn pump(&self, max: usize) -> usize {
+        self.completions
+            .try_iter()
+            .take(max)
+            .map(|complete| complete())
+            .count()
+    }
+}
+
+impl Drop for ResourceLoader {
+    fn drop(&mut self) {
+        // disconnect workers
+        self.requests = None;
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<T> LoadHandle<T> {
+    pub fn is_loading(&self) -> bool {
+        matches!(*self.0.lock(), LoadState::Loading)
+    }
+
+    /// Takes the loaded resource, or None if not yet finished or already taken
+    pub fn take(&self) -> Option<Result<T, ResourceError>> {
+        let mut state = self.0.lock();
+        match std::mem::replace(&mut *state, LoadState::Taken) {
+            LoadState::Loaded(result) => Some(result),
+            other => {
+                *state = other;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::mpsc::channel;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::error::ResourceErrorKind;
+
+    /// Reads nothing, slowly
+    struct Slow;
+
+    impl ReadResource for Slow {
+        fn read_resource(_: impl AsRef<ResourcePath>) -> Result<Self, ResourceError> {
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(Slow)
+        }
+    }
+
+    fn temp_file(name: &str, contents: &str) -> ResourcePath {
+        let dir = std::env::temp_dir().join(format!("nn-loader-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        ResourcePath::on_disk(path, 0)
+    }
+
+    /// Pumps until `count` are finished, checking no more than `max` are finished at once
+    fn pump_all(loader: &ResourceLoader, max: usize, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut finished = 0;
+        while finished < count {
+            assert!(Instant::now() < deadline, "timed out waiting for resources");
+            let n = loader.pump(max);
+            assert!(n <= max);
+            finished += n;
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(finished, count);
+    }
+
+    #[test]
+    fn pump_limit() {
+        let loader = ResourceLoader::new(2);
+        let handles = (0..5)
+            .map(|_| loader.request::<Slow>(temp_file("limit", "")))
+            .collect::<Vec<_>>();
+
+        // let everything load before pumping
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(handles.iter().all(LoadHandle::is_loading));
+        assert_eq!(loader.pump(2), 2);
+        assert_eq!(handles.iter().filter(|h| !h.is_loading()).count(), 2);
+        assert_eq!(loader.pump(2), 2);
+        assert_eq!(loader.pump(2), 1);
+        assert_eq!(loader.pump(2), 0);
+        assert!(handles.iter().all(|h| !h.is_loading()));
+    }
+
+    #[test]
+    fn handle_lifecycle() {
+        let loader = ResourceLoader::new(1);
+        let handle = loader.request::<String>(temp_file("lifecycle", "hello"));
+
+        assert!(handle.is_loading());
+        assert!(handle.take().is_none());
+        assert!(handle.is_loading());
+
+        pump_all(&loader, 1, 1);
+        assert!(!handle.is_loading());
+        assert_eq!(handle.take().unwrap().unwrap(), "hello");
+
+        // taken
+        assert!(!handle.is_loading());
+        assert!(handle.take().is_none());
+    }
+
+    #[test]
+    fn error_through_handle() {
+        let loader = ResourceLoader::new(1);
+        let missing = PathBuf::from("/definitely/not/a/resource.ron");
+        let handle = loader.request::<String>(ResourcePath::on_disk(missing.clone(), 0));
+
+        pump_all(&loader, 1, 1);
+        let err = handle.take().unwrap().unwrap_err();
+        assert_eq!(err.0, missing);
+        assert!(matches!(err.1, ResourceErrorKind::Io(_)));
+    }
+
+    #[test]
+    fn drop_with_queued_requests() {
+        let loader = ResourceLoader::new(1);
+        let handles = (0..10)
+            .map(|_| loader.request::<Slow>(temp_file("drop", "")))
+            .collect::<Vec<_>>();
+
+        let (tx, rx) = channel();
+        std::thread::spawn(move || {
+            drop(loader);
+            let _ = tx.send(());
+        });
+
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("dropping loader hung");
+
+        // never pumped
+        assert!(handles.iter().all(LoadHandle::is_loading));
+    }
+}
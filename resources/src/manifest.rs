@@ -0,0 +1,354 @@
+use crate::container::{ReadResource, ResourcePath};
+use crate::error::ResourceError;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+/// Content hashes of a set of resources, e.g. those in use when a save was created, to detect
+/// changed or removed resources and mods when it is loaded again. Hashes and resource paths are
+/// stable across platforms and builds.
+///
+/// Entries are keyed by where the resource came from as well as its path, as a mod's resources
+/// have the same paths as the base resources they override.
+///
+/// To persist it, either enable the serialize feature or store [entries](Self::entries) and
+/// collect them back into a manifest
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serialize",
+    serde(from = "Vec<ManifestEntry>", into = "Vec<ManifestEntry>")
+)]
+pub struct ResourceManifest {
+    /// (origin, resource path with / separators) -> content hash
+    entries: BTreeMap<(ResourceOrigin, String), u64>,
+}
+
+/// Where a resource in the manifest came from
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResourceOrigin {
+    Base,
+    /// Mod name
+    Mod(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestEntry {
+    pub origin: ResourceOrigin,
+    /// Always uses / separators
+    pub resource: String,
+    pub hash: u64,
+}
+
+/// A resource read only to hash its contents, for use with [recurse](crate::recurse)
+pub struct HashedResource {
+    pub resource: PathBuf,
+    pub hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestMismatch {
+    /// All resources of this mod are no longer present
+    ModRemoved(String),
+    /// Resources of this mod are present but none were in the manifest
+    ModAdded(String),
+    /// In the manifest but no longer present
+    Missing(ResourceOrigin, PathBuf),
+    /// Present in both but with different contents
+    Changed(ResourceOrigin, PathBuf),
+    /// Present now but not in the manifest
+    Added(ResourceOrigin, PathBuf),
+}
+
+impl ResourceManifest {
+    pub fn add(&mut self, origin: ResourceOrigin, resource: HashedResource) {
+        self.entries
+            .insert((origin, portable_path(&resource.resource)), resource.hash);
+    }
+
+    pub fn hash(&self, origin: &ResourceOrigin, resource: &Path) -> Option<u64> {
+        // TODO avoid clone for lookup
+        self.entries
+            .get(&(origin.clone(), portable_path(resource)))
+            .copied()
+    }
+
+    /// Sorted by origin then resource path
+    pub fn entries(&self) -> impl Iterator<Item = ManifestEntry> + '_ {
+        self.entries
+            .iter()
+            .map(|((origin, resource), hash)| ManifestEntry {
+                origin: origin.clone(),
+                resource: resource.clone(),
+                hash: *hash,
+            })
+    }
+
+    /// Names of all mods with resources in the manifest, sorted
+    pub fn mods(&self) -> BTreeSet<&str> {
+        self.entries
+            .keys()
+            .filter_map(|(origin, _)| match origin {
+                ResourceOrigin::Mod(name) => Some(name.as_str()),
+                ResourceOrigin::Base => None,
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Compares this (expected) manifest against the current resources. Removed and added mods
+    /// are reported once rather than per resource, and come first sorted by name. The rest are
+    /// sorted by origin then resource path
+    pub fn verify(&self, current: &ResourceManifest) -> Vec<ManifestMismatch> {
+        let (expected_mods, current_mods) = (self.mods(), current.mods());
+        let is_removed = |origin: &ResourceOrigin| match origin {
+            ResourceOrigin::Mod(name) => !current_mods.contains(name.as_str()),
+            ResourceOrigin::Base => false,
+        };
+        let is_added = |origin: &ResourceOrigin| match origin {
+            ResourceOrigin::Mod(name) => !expected_mods.contains(name.as_str()),
+            ResourceOrigin::Base => false,
+        };
+
+        let mut mismatches = Vec::new();
+        mismatches.extend(
+            expected_mods
+                .difference(&current_mods)
+                .map(|name| ManifestMismatch::ModRemoved((*name).to_owned())),
+        );
+        mismatches.extend(
+            current_mods
+                .difference(&expected_mods)
+                .map(|name| ManifestMismatch::ModAdded((*name).to_owned())),
+        );
+
+        let mut resources = Vec::new();
+        for ((origin, resource), hash) in &self.entries {
+            if is_removed(origin) {
+                continue;
+            }
+
+            let key = (origin.clone(), resource.clone());
+            match current.entries.get(&key) {
+                None => resources.push(ManifestMismatch::Missing(key.0, key.1.into())),
+                Some(current_hash) if current_hash != hash => {
+                    resources.push(ManifestMismatch::Changed(key.0, key.1.into()))
+                }
+                _ => {}
+            }
+        }
+
+        resources.extend(
+            current
+                .entries
+                .keys()
+                .filter(|key| !is_added(&key.0) && !self.entries.contains_key(*key))
+                .map(|(origin, resource)| ManifestMismatch::Added(origin.clone(), resource.into())),
+        );
+
+        resources.sort_unstable_by(|a, b| a.resource().cmp(&b.resource()));
+        mismatches.extend(resources);
+        mismatches
+    }
+}
+
+impl FromIterator<ManifestEntry> for ResourceManifest {
+    fn from_iter<T: IntoIterator<Item = ManifestEntry>>(iter: T) -> Self {
+        Self {
+            entries: iter
+                .into_iter()
+                .map(|e| ((e.origin, e.resource), e.hash))
+                .collect(),
+        }
+    }
+}
+
+/// All from the base resources
+impl FromIterator<HashedResource> for ResourceManifest {
+    fn from_iter<T: IntoIterator<Item = HashedResource>>(iter: T) -> Self {
+        let mut manifest = Self::default();
+        iter.into_iter()
+            .for_each(|r| manifest.add(ResourceOrigin::Base, r));
+        manifest
+    }
+}
+
+impl From<Vec<ManifestEntry>> for ResourceManifest {
+    fn from(entries: Vec<ManifestEntry>) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl From<ResourceManifest> for Vec<ManifestEntry> {
+    fn from(manifest: ResourceManifest) -> Self {
+        manifest.entries().collect()
+    }
+}
+
+impl ManifestMismatch {
+    /// Origin and path of the mismatched resource, None for a whole mod
+    pub fn resource(&self) -> Option<(&ResourceOrigin, &PathBuf)> {
+        match self {
+            ManifestMismatch::ModRemoved(_) | ManifestMismatch::ModAdded(_) => None,
+            ManifestMismatch::Missing(o, r)
+            | ManifestMismatch::Changed(o, r)
+            | ManifestMismatch::Added(o, r) => Some((o, r)),
+        }
+    }
+}
+
+impl ReadResource for HashedResource {
+    fn read_resource(path: impl AsRef<ResourcePath>) -> Result<Self, ResourceError> {
+        let path = path.as_ref();
+        let bytes = Vec::<u8>::read_resource(path)?;
+        Ok(HashedResource {
+            resource: path.resource_path(),
+            hash: fnv1a(&bytes),
+        })
+    }
+}
+
+/// Joins the components with / regardless of platform
+fn portable_path(resource: &Path) -> String {
+    let mut path = String::new();
+    for component in resource.components() {
+        if let Component::Normal(c) = component {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(&c.to_string_lossy());
+        }
+    }
+    path
+}
+
+/// std's hasher is not guaranteed to be stable between releases
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes
+        .iter()
+        .fold(OFFSET, |hash, b| (hash ^ *b as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashed(resource: &str, contents: &[u8]) -> HashedResource {
+        HashedResource {
+            resource: resource.into(),
+            hash: fnv1a(contents),
+        }
+    }
+
+    fn manifest(resources: &[(Option<&str>, &str, &[u8])]) -> ResourceManifest {
+        let mut manifest = ResourceManifest::default();
+        for (origin, resource, contents) in resources {
+            let origin = match origin {
+                None => ResourceOrigin::Base,
+                Some(name) => ResourceOrigin::Mod((*name).to_owned()),
+            };
+            manifest.add(origin, hashed(resource, contents));
+        }
+        manifest
+    }
+
+    fn base(resource: &str) -> (ResourceOrigin, PathBuf) {
+        (ResourceOrigin::Base, resource.into())
+    }
+
+    fn of_mod(name: &str, resource: &str) -> (ResourceOrigin, PathBuf) {
+        (ResourceOrigin::Mod(name.to_owned()), resource.into())
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let manifest = manifest(&[
+            (None, "definitions/nested/a.ron", b"a"),
+            (Some("cool"), "definitions/nested/a.ron", b"modded a"),
+            (None, "shaders/b.glsl", b"b"),
+        ]);
+
+        let entries = manifest.entries().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].resource, "definitions/nested/a.ron");
+
+        let loaded: ResourceManifest = entries.into_iter().collect();
+        assert_eq!(loaded, manifest);
+        assert!(loaded.verify(&manifest).is_empty());
+
+        let nested = Path::new("definitions").join("nested").join("a.ron");
+        assert_eq!(
+            loaded.hash(&ResourceOrigin::Base, &nested),
+            Some(fnv1a(b"a"))
+        );
+        assert_eq!(
+            loaded.hash(&ResourceOrigin::Mod("cool".to_owned()), &nested),
+            Some(fnv1a(b"modded a"))
+        );
+    }
+
+    #[test]
+    fn verify_mismatches() {
+        let saved = manifest(&[
+            (None, "a.ron", b"a"),
+            (None, "b.ron", b"b"),
+            (None, "c.ron", b"c"),
+            (Some("cool"), "x.ron", b"x"),
+        ]);
+        let current = manifest(&[
+            (None, "b.ron", b"b"),
+            (None, "c.ron", b"changed"),
+            (None, "d.ron", b"d"),
+            (Some("cool"), "x.ron", b"x"),
+            (Some("cool"), "y.ron", b"y"),
+        ]);
+
+        let (a, c, d, y) = (
+            base("a.ron"),
+            base("c.ron"),
+            base("d.ron"),
+            of_mod("cool", "y.ron"),
+        );
+        assert_eq!(
+            saved.verify(&current),
+            vec![
+                ManifestMismatch::Missing(a.0, a.1),
+                ManifestMismatch::Changed(c.0, c.1),
+                ManifestMismatch::Added(d.0, d.1),
+                ManifestMismatch::Added(y.0, y.1),
+            ]
+        );
+    }
+
+    #[test]
+    fn mod_removed() {
+        // mod overrides a base resource with the same path
+        let saved = manifest(&[
+            (None, "definitions/x.ron", b"x"),
+            (Some("old"), "definitions/x.ron", b"modded x"),
+            (Some("old"), "definitions/y.ron", b"y"),
+        ]);
+        let current = manifest(&[
+            (None, "definitions/x.ron", b"x"),
+            (Some("new"), "definitions/z.ron", b"z"),
+        ]);
+
+        assert_eq!(
+            saved.verify(&current),
+            vec![
+                ManifestMismatch::ModRemoved("old".to_owned()),
+                ManifestMismatch::ModAdded("new".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(feature = "archive")]
+use crate::archive::ResourceArchive;
+use crate::container::{ResourceContainer, ResourceFile, ResourcePath};
+use crate::error::ResourceError;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Overlays in-memory files on top of another container, e.g. to inject test definitions without
+/// touching the filesystem. In-memory files take precedence over the base container's
+#[derive(Clone)]
+pub struct ResourceOverlay<R> {
+    base: R,
+    /// Relative to the container directory
+    files: HashMap<PathBuf, Arc<[u8]>>,
+}
+
+impl<R: ResourceContainer> ResourceOverlay<R> {
+    pub fn new(base: R) -> Self {
+        Self {
+            base,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Path is relative to the container directory. Replaces any existing in-memory file
+    pub fn add_file(&mut self, file: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) {
+        self.files.insert(file.into(), contents.into().into());
+    }
+
+    pub fn with_file(mut self, file: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) -> Self {
+        self.add_file(file, contents);
+        self
+    }
+
+    pub fn base(&self) -> &R {
+        &self.base
+    }
+}
+
+impl<R: ResourceContainer> ResourceContainer for ResourceOverlay<R> {
+    const DIR: &'static str = R::DIR;
+
+    fn path(&self) -> &Path {
+        self.base.path()
+    }
+
+    fn component_offset(&self) -> usize {
+        self.base.component_offset()
+    }
+
+    #[cfg(feature = "archive")]
+    fn archive(&self) -> Option<&ResourceArchive> {
+        self.base.archive()
+    }
+
+    fn in_memory_files(&self) -> Vec<ResourcePath> {
+        let files = self
+            .files
+            .iter()
+            .map(|(file, contents)| {
+                ResourcePath::in_memory(
+                    self.path().join(file),
+                    self.component_offset(),
+                    contents.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // drop files from a nested overlay that this one overrides
+        let overridden = files
+            .iter()
+            .map(ResourcePath::resource_path)
+            .collect::<HashSet<_>>();
+        let mut base_files = self.base.in_memory_files();
+        base_files.retain(|path| !overridden.contains(&path.resource_path()));
+
+        base_files.extend(files);
+        base_files
+    }
+
+    fn get_file(&self, file: impl AsRef<ResourceFile>) -> Result<ResourcePath, ResourceError> {
+        let file = file.as_ref();
+        match self.files.get(file.as_path()) {
+            Some(contents) => Ok(ResourcePath::in_memory(
+                self.path().join(file.as_path()),
+                self.component_offset(),
+                contents.clone(),
+            )),
+            None => self.base.get_file(file),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{recurse, ResourceContainer, ResourceOverlay, Resources};
+
+    #[test]
+    fn nested_overlay_overrides() {
+        let game_dir = std::env::temp_dir().join(format!("nn-overlay-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&game_dir);
+        std::fs::create_dir_all(game_dir.join("resources/definitions")).unwrap();
+        std::fs::write(game_dir.join("resources/definitions/a.ron"), "disk a").unwrap();
+
+        let definitions = Resources::new(&game_dir).unwrap().definitions().unwrap();
+        let inner = ResourceOverlay::new(definitions)
+            .with_file("a.ron", "inner a")
+            .with_file("b.ron", "inner b");
+        let outer = ResourceOverlay::new(inner).with_file("b.ron", "outer b");
+
+        let mut all = recurse::<_, String>(&outer, "ron")
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, vec!["inner a", "outer b"]);
+
+        assert_eq!(outer.in_memory_files().len(), 2);
+    }
+}
@@ -1,9 +1,16 @@
 //! Resource filesystem structure declaration for the game
 
-use crate::container::ResourceContainer;
+#[cfg(feature = "archive")]
+use crate::archive::ResourceArchive;
+use crate::container::{ContainerArchive, ResourceContainer};
 use crate::error::{ResourceError, ResourceErrorKind};
 use crate::{child, resources};
+use misc::*;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use walkdir::WalkDir;
 
 resources!(Resources, "resources");
 
@@ -11,6 +18,12 @@ resources!(Definitions, "definitions");
 resources!(WorldGen, "worldgen");
 resources!(Shaders, "shaders");
 resources!(Fonts, "fonts");
+resources!(Scenarios, "scenarios");
+resources!(Sounds, "sounds");
+resources!(Scripts, "scripts");
+resources!(Mods, "mods");
+resources!(Textures, "textures");
+resources!(Lang, "lang");
 
 impl Resources {
     pub fn new(game_dir: impl AsRef<Path>) -> Result<Self, ResourceError> {
@@ -20,13 +33,255 @@ impl Resources {
         Ok(Self {
             path,
             component_offset,
+            archive: None,
         })
     }
 
+    /// Resources are read from the archive unless overridden by loose files in the resources
+    /// directory, which does not need to exist
+    #[cfg(feature = "archive")]
+    pub fn with_archive(game_dir: impl AsRef<Path>, archive: ResourceArchive) -> Self {
+        let path = game_dir.as_ref().join(Self::DIR);
+        let component_offset = path.components().count();
+        Self {
+            path,
+            component_offset,
+            archive: Some(archive),
+        }
+    }
+
     child!(definitions, Definitions);
     child!(world_gen, WorldGen);
     child!(shaders, Shaders);
     child!(fonts, Fonts);
+    child!(scenarios, Scenarios);
+    child!(sounds, Sounds);
+    child!(scripts, Scripts);
+    child!(mods, Mods);
+    child!(textures, Textures);
+    child!(lang, Lang);
+}
+
+/// A single mod in `resources/mods`, laid out like the base resources directory. A mod is a
+/// directory, or with the archive feature a zip of one, or both. Resource paths are relative to
+/// the mod's root, so they match the base resource they override. See [Mods::layers] to layer
+/// mods over the base resources
+#[derive(Clone)]
+pub struct Mod {
+    name: String,
+    /// Mod directory, which only needs to exist if there is no archive
+    path: PathBuf,
+    component_offset: usize,
+    /// Zipped mod, overridden by loose files in the mod directory
+    archive: ContainerArchive,
+}
+
+/// A resource provided by more than one mod. The last mod in load order wins when layered with
+/// [recurse_layered](crate::recurse_layered)
+#[derive(Debug)]
+pub struct ModConflict {
+    pub resource: PathBuf,
+    /// In load order
+    pub mods: Vec<String>,
+}
+
+impl Mods {
+    /// Optional file in the mods directory listing mod names in load order, one per line. Lines
+    /// starting with # are ignored
+    pub const LOAD_ORDER_FILE: &'static str = "load_order.txt";
+
+    /// Extension of zipped mods, which are only loaded with the archive feature
+    pub const ARCHIVE_EXTENSION: &'static str = "zip";
+
+    /// Finds all mods in load order. If there is no load order file, all mods are loaded in
+    /// alphabetical order. Mods that are missing or not in the load order are skipped
+    pub fn discover(&self) -> Result<Vec<Mod>, ResourceError> {
+        let io_err =
+            |path: &Path, e| ResourceError(path.to_owned(), ResourceErrorKind::Io(Arc::new(e)));
+
+        let mut available = std::fs::read_dir(&self.path)
+            .and_then(|entries| {
+                entries
+                    .filter_map(|e| match e {
+                        Ok(e) if e.path().is_dir() => Some(Ok(e.file_name())),
+                        Ok(e)
+                            if cfg!(feature = "archive")
+                                && e.path().is_file()
+                                && e.path().extension()
+                                    == Some(OsStr::new(Self::ARCHIVE_EXTENSION)) =>
+                        {
+                            e.path().file_stem().map(|name| Ok(name.to_owned()))
+                        }
+                        Ok(_) => None,
+                        Err(e) => Some(Err(e)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| io_err(&self.path, e))?
+            .into_iter()
+            .filter_map(|name| match name.into_string() {
+                Ok(name) => Some(name),
+                Err(name) => {
+                    warn!("skipping mod with non-utf8 name"; "name" => ?name);
+                    None
+                }
+            })
+            .collect_vec();
+
+        // a mod can be both a directory and an archive
+        available.sort();
+        available.dedup();
+
+        let load_order_path = self.path.join(Self::LOAD_ORDER_FILE);
+        let names = if load_order_path.is_file() {
+            let load_order = std::fs::read_to_string(&load_order_path)
+                .map_err(|e| io_err(&load_order_path, e))?;
+
+            let mut names: Vec<String> = Vec::new();
+            for name in load_order
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            {
+                if names.iter().any(|n| n == name) {
+                    warn!("mod is in load order more than once, ignoring duplicate"; "mod" => name);
+                } else {
+                    names.push(name.to_owned());
+                }
+            }
+
+            for unlisted in available.iter().filter(|name| !names.contains(name)) {
+                warn!("mod is not in load order, skipping"; "mod" => unlisted);
+            }
+
+            names
+        } else {
+            available
+        };
+
+        Ok(names
+            .into_iter()
+            .filter_map(|name| self.load_mod(name))
+            .collect())
+    }
+
+    /// The given base directory followed by the same directory of each mod that has it, in load
+    /// order, for use with [recurse_layered](crate::recurse_layered) and
+    /// [get_layered_file](crate::get_layered_file), e.g.
+    /// `Mods::layers(resources.definitions()?, &mods, Mod::definitions)`
+    pub fn layers<R>(
+        base: R,
+        mods: &[Mod],
+        child: impl Fn(&Mod) -> Result<R, ResourceError>,
+    ) -> Vec<R> {
+        std::iter::once(base)
+            .chain(mods.iter().filter_map(|m| child(m).ok()))
+            .collect()
+    }
+
+    /// None if the mod is missing or its archive can't be opened
+    fn load_mod(&self, name: String) -> Option<Mod> {
+        let path = self.path.join(&name);
+        let component_offset = path.components().count();
+
+        #[cfg(feature = "archive")]
+        let archive = {
+            let archive_path = self
+                .path
+                .join(format!("{}.{}", name, Self::ARCHIVE_EXTENSION));
+            if archive_path.is_file() {
+                match ResourceArchive::open(&archive_path) {
+                    Ok(archive) => Some(archive),
+                    Err(err) => {
+                        warn!("failed to open mod archive, skipping"; "mod" => &name, "error" => %err);
+                        return None;
+                    }
+                }
+            } else {
+                None
+            }
+        };
+
+        #[cfg(not(feature = "archive"))]
+        let archive = None;
+
+        if !path.is_dir() && archive.is_none() {
+            warn!("mod is missing, skipping"; "mod" => &name);
+            return None;
+        }
+
+        Some(Mod {
+            name,
+            path,
+            component_offset,
+            archive,
+        })
+    }
+}
+
+impl Mod {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    child!(definitions, Definitions);
+    child!(scenarios, Scenarios);
+    child!(scripts, Scripts);
+
+    /// All files provided by this mod, relative to the mod's root
+    fn resource_paths(&self) -> HashSet<PathBuf> {
+        #[cfg(feature = "archive")]
+        let archived = self
+            .archive
+            .iter()
+            .flat_map(|archive| archive.files_in(Path::new("")));
+        #[cfg(not(feature = "archive"))]
+        let archived = std::iter::empty();
+
+        // directory may only exist in an archive
+        let dir = self.path.is_dir().then(|| WalkDir::new(&self.path));
+
+        dir.into_iter()
+            .flatten()
+            .filter_map(move |e| match e {
+                Err(e) => {
+                    warn!("failed to read mod file"; "mod" => &self.name, "error" => %e);
+                    None
+                }
+                Ok(e) if e.path().is_file() => Some(
+                    e.path()
+                        .components()
+                        .skip(self.component_offset)
+                        .map(|c| c.as_os_str())
+                        .collect(),
+                ),
+                Ok(_) => None,
+            })
+            .chain(archived)
+            .collect()
+    }
+}
+
+/// Finds resources provided by more than one of the given mods, which should be in load order
+pub fn find_mod_conflicts(mods: &[Mod]) -> Vec<ModConflict> {
+    let mut providers: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for m in mods {
+        for resource in m.resource_paths() {
+            providers.entry(resource).or_default().push(m.name.clone());
+        }
+    }
+
+    let mut conflicts = providers
+        .into_iter()
+        .filter(|(_, mods)| mods.len() > 1)
+        .map(|(resource, mods)| ModConflict { resource, mods })
+        .collect_vec();
+    conflicts.sort_unstable_by(|a, b| a.resource.cmp(&b.resource));
+    conflicts
 }
 
 fn get_dir<R: AsRef<Path>, D: AsRef<Path>>(root: R, dir: D) -> Result<PathBuf, ResourceError> {
@@ -43,3 +298,204 @@ fn get_dir<R: AsRef<Path>, D: AsRef<Path>>(root: R, dir: D) -> Result<PathBuf, R
         ))
     }
 }
+
+/// Path of the child directory if it only exists in the archive
+#[cfg(feature = "archive")]
+fn get_archived_dir(
+    parent: &Path,
+    component_offset: usize,
+    archive: &Option<ResourceArchive>,
+    dir: &str,
+) -> Option<PathBuf> {
+    let path = parent.join(dir);
+    let relative: PathBuf = path.components().skip(component_offset).collect();
+    archive
+        .as_ref()
+        .filter(|archive| archive.contains_dir(&relative))
+        .map(|_| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::{
+        find_mod_conflicts, get_layered_file, recurse_layered, Mod, Mods, ReadResource, Resources,
+    };
+
+    /// Creates a game dir with the given files, relative to the resources dir
+    fn game_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let game_dir =
+            std::env::temp_dir().join(format!("nn-mods-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&game_dir);
+        std::fs::create_dir_all(game_dir.join("resources/mods")).unwrap();
+
+        for (file, contents) in files {
+            let path = game_dir.join("resources").join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        game_dir
+    }
+
+    fn names(mods: &[Mod]) -> Vec<&str> {
+        mods.iter().map(Mod::name).collect()
+    }
+
+    fn discover(game_dir: &Path) -> Vec<Mod> {
+        Resources::new(game_dir)
+            .unwrap()
+            .mods()
+            .unwrap()
+            .discover()
+            .unwrap()
+    }
+
+    #[test]
+    fn layered_over_base() {
+        let game_dir = game_dir(
+            "layered",
+            &[
+                ("definitions/a.ron", "base a"),
+                ("definitions/b.ron", "base b"),
+                ("mods/load_order.txt", "first\nsecond\nno_definitions"),
+                ("mods/first/definitions/a.ron", "first a"),
+                ("mods/first/definitions/c.ron", "first c"),
+                ("mods/second/definitions/a.ron", "second a"),
+                ("mods/no_definitions/scripts/x.lua", ""),
+            ],
+        );
+
+        let mods = discover(&game_dir);
+        let base = Resources::new(&game_dir).unwrap().definitions().unwrap();
+        let layers = Mods::layers(base, &mods, Mod::definitions);
+        assert_eq!(layers.len(), 3);
+
+        let mut all = recurse_layered::<_, String>(&layers, "ron")
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, vec!["base b", "first c", "second a"]);
+
+        let read = |file: &str| String::read_resource(get_layered_file(&layers, file).unwrap());
+        assert_eq!(read("a.ron").unwrap(), "second a");
+        assert_eq!(read("b.ron").unwrap(), "base b");
+        assert_eq!(read("c.ron").unwrap(), "first c");
+        assert!(get_layered_file(&layers, "d.ron").is_err());
+    }
+
+    #[test]
+    fn alphabetical_without_load_order() {
+        let game_dir = game_dir(
+            "alphabetical",
+            &[
+                ("mods/c/definitions/x.ron", ""),
+                ("mods/a/definitions/x.ron", ""),
+                ("mods/b/definitions/x.ron", ""),
+                ("mods/not_a_mod.txt", ""),
+            ],
+        );
+
+        assert_eq!(names(&discover(&game_dir)), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn load_order() {
+        let game_dir = game_dir(
+            "load-order",
+            &[
+                (
+                    "mods/load_order.txt",
+                    "# comment\n\nc\n  \n  a  \n# b\nmissing\nc\n",
+                ),
+                ("mods/a/definitions/x.ron", ""),
+                ("mods/b/definitions/x.ron", ""),
+                ("mods/c/definitions/x.ron", ""),
+            ],
+        );
+
+        // b is unlisted, missing doesn't exist, c is duplicated
+        assert_eq!(names(&discover(&game_dir)), vec!["c", "a"]);
+    }
+
+    #[test]
+    fn conflicts_in_load_order() {
+        let game_dir = game_dir(
+            "conflicts",
+            &[
+                ("mods/load_order.txt", "c\na\nb\nc"),
+                ("mods/a/definitions/y.ron", ""),
+                ("mods/a/definitions/unique.ron", ""),
+                ("mods/b/definitions/x.ron", ""),
+                ("mods/c/definitions/x.ron", ""),
+                ("mods/c/definitions/y.ron", ""),
+                ("mods/c/scripts/z.lua", ""),
+            ],
+        );
+
+        let conflicts = find_mod_conflicts(&discover(&game_dir))
+            .into_iter()
+            .map(|c| (c.resource, c.mods))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conflicts,
+            vec![
+                (
+                    PathBuf::from("definitions/x.ron"),
+                    vec!["c".to_owned(), "b".to_owned()]
+                ),
+                (
+                    PathBuf::from("definitions/y.ron"),
+                    vec!["c".to_owned(), "a".to_owned()]
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn directory_and_archive() {
+        use std::io::Write;
+        use zip::write::FileOptions;
+        use zip::ZipWriter;
+
+        use crate::{recurse, ResourceContainer};
+
+        let game_dir = game_dir(
+            "archive",
+            &[
+                ("mods/both/definitions/a.ron", "disk a"),
+                ("mods/dir/definitions/a.ron", "dir a"),
+            ],
+        );
+
+        let zip_file = std::fs::File::create(game_dir.join("resources/mods/both.zip")).unwrap();
+        let mut zip = ZipWriter::new(zip_file);
+        for (file, contents) in [
+            ("definitions/a.ron", "zip a"),
+            ("definitions/b.ron", "zip b"),
+        ] {
+            zip.start_file(file, FileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let mods = discover(&game_dir);
+        assert_eq!(names(&mods), vec!["both", "dir"]);
+
+        let both = mods[0].definitions().unwrap();
+        assert!(both.archive().is_some());
+        let mut all = recurse::<_, String>(&both, "ron")
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, vec!["disk a", "zip b"]);
+
+        // a mod doesn't conflict with itself
+        let conflicts = find_mod_conflicts(&mods);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].resource, PathBuf::from("definitions/a.ron"));
+        assert_eq!(conflicts[0].mods, vec!["both", "dir"]);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::container::ResourcePath;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Polls the modification times of watched resources, for live reloading during development.
+/// Only resources on disk can be watched
+#[derive(Default)]
+pub struct ResourceWatcher {
+    watched: Vec<Watched>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceChange {
+    /// Relative to the root resource path, see [ResourcePath::resource_path]
+    pub resource: PathBuf,
+    pub kind: ResourceChangeKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResourceChangeKind {
+    /// Modified or recreated
+    Modified,
+    Removed,
+}
+
+struct Watched {
+    file: PathBuf,
+    resource: PathBuf,
+    /// None if missing
+    modified: Option<SystemTime>,
+}
+
+impl ResourceWatcher {
+    /// Returns false if the resource is not on disk
+    pub fn watch(&mut self, path: &ResourcePath) -> bool {
+        let file = match path.file_path() {
+            Some(file) => file,
+            None => return false,
+        };
+
+        if !self.watched.iter().any(|w| w.file == file) {
+            self.watched.push(Watched {
+                file: file.to_owned(),
+                resource: path.resource_path(),
+                modified: modified_time(file),
+            });
+        }
+
+        true
+    }
+
+    pub fn unwatch(&mut self, path: &ResourcePath) {
+        if let Some(file) = path.file_path() {
+            self.watched.retain(|w| w.file != file);
+        }
+    }
+
+    /// Checks all watched resources for changes since the last poll
+    pub fn poll(&mut self) -> impl Iterator<Item = ResourceChange> + '_ {
+        self.watched.iter_mut().filter_map(|watched| {
+            let modified = modified_time(&watched.file);
+            if modified == watched.modified {
+                return None;
+            }
+
+            watched.modified = modified;
+            let kind = if modified.is_some() {
+                ResourceChangeKind::Modified
+            } else {
+                ResourceChangeKind::Removed
+            };
+
+            Some(ResourceChange {
+                resource: watched.resource.clone(),
+                kind,
+            })
+        })
+    }
+}
+
+fn modified_time(file: &Path) -> Option<SystemTime> {
+    std::fs::metadata(file).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{ResourceContainer, ResourceOverlay, Resources};
+
+    /// Rewrites the file with a modification time distinct from any previous write
+    fn write(file: &Path, contents: &str, age: u64) {
+        std::fs::write(file, contents).unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + age);
+        File::options()
+            .write(true)
+            .open(file)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn watch_changes() {
+        let game_dir = std::env::temp_dir().join(format!("nn-watcher-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&game_dir);
+        let file = game_dir.join("resources/definitions/a.ron");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        write(&file, "a", 0);
+
+        let definitions = Resources::new(&game_dir).unwrap().definitions().unwrap();
+        let overlay = ResourceOverlay::new(definitions).with_file("b.ron", "b");
+
+        let mut watcher = ResourceWatcher::default();
+
+        // in-memory only
+        assert!(!watcher.watch(&overlay.get_file("b.ron").unwrap()));
+
+        // watching twice is the same as once
+        let path = overlay.base().get_file("a.ron").unwrap();
+        assert!(watcher.watch(&path));
+        assert!(watcher.watch(&path));
+        assert_eq!(watcher.poll().count(), 0);
+
+        let change = |kind| ResourceChange {
+            resource: PathBuf::from("definitions/a.ron"),
+            kind,
+        };
+
+        write(&file, "aa", 1);
+        assert_eq!(
+            watcher.poll().collect::<Vec<_>>(),
+            vec![change(ResourceChangeKind::Modified)]
+        );
+        assert_eq!(watcher.poll().count(), 0);
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(
+            watcher.poll().collect::<Vec<_>>(),
+            vec![change(ResourceChangeKind::Removed)]
+        );
+        assert_eq!(watcher.poll().count(), 0);
+
+        write(&file, "aaa", 2);
+        assert_eq!(
+            watcher.poll().collect::<Vec<_>>(),
+            vec![change(ResourceChangeKind::Modified)]
+        );
+
+        watcher.unwatch(&path);
+        write(&file, "aaaa", 3);
+        assert_eq!(watcher.poll().count(), 0);
+    }
+}
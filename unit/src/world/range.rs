@@ -157,6 +157,32 @@ impl<P: RangePosition> WorldRange<P> {
 
         ax <= x && x <= bx && ay <= y && y <= by && az <= z && z <= bz
     }
+
+    /// Inclusive range covered by both, or None if they don't overlap
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        fn overlap<T: PartialOrd>((a0, b0): (T, T), (a1, b1): (T, T)) -> Option<(T, T)> {
+            let from = if a0 > a1 { a0 } else { a1 };
+            let to = if b0 < b1 { b0 } else { b1 };
+            (from <= to).then_some((from, to))
+        }
+
+        let (x0, y0, z0) = self.ranges();
+        let (x1, y1, z1) = other.ranges();
+
+        let (ax, bx) = overlap(x0, x1)?;
+        let (ay, by) = overlap(y0, y1)?;
+        let (az, bz) = overlap(z0, z1)?;
+
+        // derived from valid `P`s
+        Some(Self::Range(
+            P::new_unchecked((ax, ay, az)),
+            P::new_unchecked((bx, by, bz)),
+        ))
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
 }
 
 impl<XY: Copy, Z: Copy, P: RangePosition + Add<(XY, XY, Z), Output = P>> Add<(XY, XY, Z)>
@@ -378,6 +404,26 @@ mod tests {
         assert_eq!(range.count(), 2 * 2 * 3);
     }
 
+    #[test]
+    fn intersection() {
+        let a = WorldPositionRange::with_inclusive_range((0, 0, 0), (4, 4, 2));
+        let b = WorldPositionRange::with_inclusive_range((6, 3, 1), (3, 8, 5));
+        assert_eq!(
+            a.intersection(&b).map(|r| r.ranges()),
+            Some(((3, 4), (3, 4), (1, 2)))
+        );
+        assert!(b.intersects(&a));
+
+        // touching
+        let c = WorldPositionRange::with_single((4, 4, 2));
+        assert_eq!(a.intersection(&c).map(|r| r.count()), Some(1));
+
+        // disjoint in only one axis
+        let d = WorldPositionRange::with_inclusive_range((0, 0, 3), (4, 4, 5));
+        assert!(a.intersection(&d).is_none());
+        assert!(!d.intersects(&a));
+    }
+
     #[test]
     fn outline_no_overlap() {
         let range = WorldPositionRange::with_inclusive_range((0, 0, 0), (3, 3, 3));
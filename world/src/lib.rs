@@ -12,8 +12,11 @@ pub use self::context::{
 };
 pub use self::mesh::BaseVertex;
 pub use self::navigation::{EdgeCost, NavigationError, SearchGoal, WorldArea, WorldPath};
-pub use self::viewer::{SliceRange, WorldViewer};
-pub use self::world::{helpers, ExplorationFilter, ExplorationResult, World, WorldChangeEvent};
+pub use self::viewer::{MeshRegenStats, SliceRange, WorldViewer};
+pub use self::world::{
+    helpers, BlockAccess, ExplorationFilter, ExplorationResult, NavigationFilter, World,
+    WorldChangeEvent,
+};
 pub use self::world_ref::{InnerWorldRef, InnerWorldRefMut, WorldRef};
 pub use occlusion::{BlockOcclusion, OcclusionFace};
 pub use ray::VoxelRay;
@@ -3,8 +3,8 @@ use std::fmt::{Debug, Formatter};
 use std::iter::once;
 
 use petgraph::graph::EdgeIndex;
-use petgraph::stable_graph::StableGraph;
-use petgraph::visit::Visitable;
+use petgraph::stable_graph::{EdgeReference, StableGraph};
+use petgraph::visit::{EdgeRef, Visitable};
 use petgraph::Directed;
 
 use misc::*;
@@ -15,7 +15,7 @@ use crate::navigation::path::AreaPathNode;
 use crate::navigation::search::{astar, SearchContext};
 use crate::navigation::{AreaPath, WorldArea};
 use crate::neighbour::NeighbourOffset;
-use crate::EdgeCost;
+use crate::{EdgeCost, NavigationFilter};
 
 type AreaNavGraph = StableGraph<AreaNavNode, AreaNavEdge, Directed, u32>;
 type NodeIndex = petgraph::prelude::NodeIndex<u32>;
@@ -154,18 +154,35 @@ impl AreaNavEdge {
         })
     }
 
+    /// The block in the entered chunk that is reached from the given exit block
+    pub fn entry_for_exit(&self, exit: BlockPosition) -> BlockPosition {
+        let extended = self.direction.extend_across_boundary_aligned(exit);
+        extended.above_by(self.cost.z_offset())
+    }
+
     /// Finds the block along the full width of the port that is closest to the given source block.
     pub fn exit_closest(self, source: BlockPosition) -> BlockPosition {
+        self.exit_closest_accessible(source, |_, _| true)
+            .expect("exit cannot be zero width")
+    }
+
+    /// Finds the block along the full width of the port that is closest to the given source
+    /// block, where both it and the block it enters in the next chunk are accessible, checked with
+    /// `accessible(exit, entry)`. None if no exit block is accessible
+    pub fn exit_closest_accessible(
+        self,
+        source: BlockPosition,
+        mut accessible: impl FnMut(BlockPosition, BlockPosition) -> bool,
+    ) -> Option<BlockPosition> {
         let (src_x, src_y) = (source.x() as i16, source.y() as i16);
         self.iter_exit_blocks()
+            .map(|b| b.to_block_position(self.exit.z()))
+            .filter(|b| accessible(*b, self.entry_for_exit(*b)))
             .min_by_key(|candidate| {
-                let (x, y) = candidate.xy();
-                let dx = (x as i16 - src_x).abs();
-                let dy = (y as i16 - src_y).abs();
+                let dx = (candidate.x() as i16 - src_x).abs();
+                let dy = (candidate.y() as i16 - src_y).abs();
                 dx + dy
             })
-            .expect("exit cannot be zero width")
-            .to_block_position(self.exit.z())
     }
 
     pub fn contains(&self, block: BlockPosition) -> bool {
@@ -186,6 +203,19 @@ impl AreaGraph {
         start: WorldArea,
         goal: WorldArea,
         context: &AreaGraphSearchContext,
+    ) -> Result<AreaPath, AreaPathError> {
+        self.find_area_path_filtered(start, goal, None, context)
+    }
+
+    /// Transitions between areas are only considered if at least one of their exit blocks and the
+    /// block it enters on the other side are both permitted by the filter. Blocks within areas are
+    /// not checked here.
+    pub(crate) fn find_area_path_filtered(
+        &self,
+        start: WorldArea,
+        goal: WorldArea,
+        filter: Option<&NavigationFilter>,
+        context: &AreaGraphSearchContext,
     ) -> Result<AreaPath, AreaPathError> {
         let src_node = self.get_node(start)?;
         let dst_node = self.get_node(goal)?;
@@ -194,11 +224,37 @@ impl AreaGraph {
         debug_assert!(self.graph.contains_node(src_node), "start: {:?}", start);
         debug_assert!(self.graph.contains_node(dst_node), "goal: {:?}", goal);
 
+        let edge_cost = |edge: EdgeReference<AreaNavEdge>| {
+            let nav_edge = edge.weight();
+            let multiplier = match filter {
+                None => 1.0,
+                Some(filter) => {
+                    // exit blocks are in the source area's chunk, entry blocks in the target's
+                    let exit_chunk = self.graph[edge.source()].0.chunk;
+                    let entry_chunk = self.graph[edge.target()].0.chunk;
+                    nav_edge
+                        .iter_exit_blocks()
+                        .filter_map(|b| {
+                            let exit = b.to_block_position(nav_edge.exit.z());
+                            let entry = nav_edge.entry_for_exit(exit);
+                            let exit_mul =
+                                filter.cost_multiplier(exit.to_world_position(exit_chunk))?;
+                            let entry_mul =
+                                filter.cost_multiplier(entry.to_world_position(entry_chunk))?;
+                            Some(exit_mul.max(entry_mul))
+                        })
+                        .min_by_key(|mul| OrderedFloat(*mul))?
+                }
+            };
+
+            Some(nav_edge.cost.weight() * multiplier) // TODO could prefer wider ports
+        };
+
         astar(
             &self.graph,
             src_node,
             |n| n == dst_node,
-            |edge| edge.weight().cost.weight(), // TODO could prefer wider ports
+            edge_cost,
             |n| {
                 // manhattan distance * chunk size, underestimates
                 let ChunkLocation(nx, ny) = &self.graph[n].0.chunk;
@@ -10,7 +10,7 @@ use unit::world::{BlockPosition, ChunkLocation, SlabIndex, SlabPosition};
 use crate::navigation::path::{BlockPath, BlockPathNode};
 use crate::navigation::search::{self, ExploreResult, SearchContext};
 use crate::navigation::{EdgeCost, SearchGoal};
-use crate::{ExplorationFilter, ExplorationResult};
+use crate::{ExplorationFilter, ExplorationResult, NavigationFilter};
 
 type BlockNavGraph = DiGraphMap<BlockNavNode, BlockNavEdge>;
 pub type BlockGraphSearchContext = SearchContext<
@@ -79,6 +79,18 @@ impl BlockGraph {
         to: BlockPosition,
         goal: SearchGoal,
         context: &BlockGraphSearchContext,
+    ) -> Result<BlockPath, BlockPathError> {
+        self.find_block_path_filtered(from, to, goal, None, context)
+    }
+
+    /// Filter is applied to every block entered, so a path may start on a forbidden block
+    pub(crate) fn find_block_path_filtered(
+        &self,
+        from: BlockPosition,
+        to: BlockPosition,
+        goal: SearchGoal,
+        filter: Option<(&NavigationFilter, ChunkLocation)>,
+        context: &BlockGraphSearchContext,
     ) -> Result<BlockPath, BlockPathError> {
         // same source and dest is a success, if not a pointless one
         if from == to {
@@ -126,7 +138,15 @@ impl BlockGraph {
             &self.graph,
             src,
             is_goal,
-            |(_, _, e)| e.0.weight(),
+            |(_, to, e)| {
+                let multiplier = match filter {
+                    None => 1.0,
+                    Some((filter, chunk)) => {
+                        filter.cost_multiplier(to.0.to_world_position(chunk))?
+                    }
+                };
+                Some(e.0.weight() * multiplier)
+            },
             heuristic,
             context,
         );
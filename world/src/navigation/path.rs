@@ -16,6 +16,9 @@ pub enum NavigationError {
     #[error("No such area {0:?}")]
     NoSuchArea(WorldArea),
 
+    #[error("No accessible exit from area {0:?}")]
+    NoAccessibleExit(WorldArea),
+
     #[error("Area navigation error: {0}")]
     AreaError(#[from] AreaPathError),
 
@@ -36,7 +36,8 @@ where
     result: Vec<(N, E)>,
 }
 
-/// Path is populated in context, left empty if search failed. On success, doesn't include goal node
+/// Path is populated in context, left empty if search failed. On success, doesn't include goal node.
+/// Edges with no cost are not traversable
 pub fn astar<G, F, H, K, IsGoal>(
     graph: G,
     start: G::NodeId,
@@ -48,7 +49,7 @@ pub fn astar<G, F, H, K, IsGoal>(
     G: IntoEdges + Visitable,
     IsGoal: FnMut(G::NodeId) -> bool,
     G::NodeId: Eq + Hash + Copy,
-    F: FnMut(G::EdgeRef) -> K,
+    F: FnMut(G::EdgeRef) -> Option<K>,
     H: FnMut(G::NodeId) -> K,
     K: Measure + Copy,
 {
@@ -85,7 +86,10 @@ pub fn astar<G, F, H, K, IsGoal>(
                 continue;
             }
 
-            let mut next_score = node_score + edge_cost(edge);
+            let mut next_score = match edge_cost(edge) {
+                Some(cost) => node_score + cost,
+                None => continue,
+            };
 
             match ctx.scores.entry(next) {
                 Occupied(ent) => {
@@ -6,7 +6,7 @@ use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, RangeInclusive};
 use unit::world::{
-    all_slabs_in_range, ChunkLocation, GlobalSliceIndex, SlabLocation, WorldPosition,
+    all_slabs_in_range, ChunkLocation, GlobalSliceIndex, SlabIndex, SlabLocation, WorldPosition,
 };
 
 #[derive(Clone)]
@@ -16,6 +16,17 @@ pub struct WorldViewer<C: WorldContext> {
     chunk_range: (ChunkLocation, ChunkLocation),
     clean_slabs: HashSet<SlabLocation>,
     requested_slabs: Vec<SlabLocation>,
+    /// Reused between mesh regenerations
+    dirty_chunks: Vec<ChunkLocation>,
+}
+
+/// Result of a single call to [WorldViewer::regenerate_dirty_chunk_meshes_with_budget]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MeshRegenStats {
+    /// Chunk meshes regenerated in this call
+    pub regenerated: usize,
+    /// Visible chunks still dirty because the budget ran out
+    pub pending: usize,
 }
 
 #[derive(Debug, Clone, Error)]
@@ -139,31 +150,68 @@ impl<C: WorldContext> WorldViewer<C> {
             chunk_range: (initial_chunk, initial_chunk), // TODO is this ok?
             clean_slabs: HashSet::with_capacity(128),
             requested_slabs: Vec::with_capacity(128),
+            dirty_chunks: Vec::with_capacity(64),
         })
     }
 
     pub fn regenerate_dirty_chunk_meshes<F: FnMut(ChunkLocation, Vec<V>), V: BaseVertex>(
         &mut self,
-        mut f: F,
+        f: F,
     ) {
+        let _ = self.regenerate_dirty_chunk_meshes_with_budget(usize::MAX, f);
+    }
+
+    /// Regenerates at most `budget` dirty chunk meshes, closest to the centre of the view first.
+    /// The rest stay dirty for a later call, so mass terrain updates are spread across frames
+    pub fn regenerate_dirty_chunk_meshes_with_budget<
+        F: FnMut(ChunkLocation, Vec<V>),
+        V: BaseVertex,
+    >(
+        &mut self,
+        budget: usize,
+        mut f: F,
+    ) -> MeshRegenStats {
         let range = self.terrain_range();
-        let world = self.world.borrow();
+        let (bottom_slab, top_slab) = (range.bottom().slab_index(), range.top().slab_index());
+
+        let mut dirty_chunks = std::mem::take(&mut self.dirty_chunks);
+        dirty_chunks.extend(
+            self.visible_slabs(range)
+                .filter_map(|slab| self.is_slab_dirty(&slab).then_some(slab.chunk)),
+        );
 
-        for dirty_chunk in self
-            .visible_slabs(range)
-            .filter_map(|slab| self.is_slab_dirty(&slab).then_some(slab.chunk))
-            .dedup()
-            .filter_map(|chunk| world.find_chunk_with_pos(chunk))
-        {
-            // TODO do mesh generation on a worker thread? or just do this bit in a parallel iter
-            let mesh = mesh::make_simple_render_mesh(dirty_chunk, range);
-            trace!("chunk mesh has {count} vertices", count = mesh.len(); dirty_chunk.pos());
-            f(dirty_chunk.pos(), mesh);
+        // closest to the centre first, sorting by chunk too to group duplicates for dedup
+        let (min, max) = self.chunk_range;
+        let (cx, cy) = ((min.0 + max.0) / 2, (min.1 + max.1) / 2);
+        dirty_chunks.sort_unstable_by_key(|c| ((c.0 - cx).abs() + (c.1 - cy).abs(), *c));
+        dirty_chunks.dedup();
+
+        let world = self.world.borrow();
+        let mut stats = MeshRegenStats::default();
+        for chunk_pos in dirty_chunks.drain(..) {
+            if let Some(chunk) = world.find_chunk_with_pos(chunk_pos) {
+                if stats.regenerated == budget {
+                    stats.pending += 1;
+                    continue;
+                }
+
+                // TODO do mesh generation on a worker thread? or just do this bit in a parallel iter
+                let mesh = mesh::make_simple_render_mesh(chunk, range);
+                trace!("chunk mesh has {count} vertices", count = mesh.len(); chunk_pos);
+                f(chunk_pos, mesh);
+                stats.regenerated += 1;
+            }
+
+            self.clean_slabs.extend(
+                (bottom_slab.as_i32()..=top_slab.as_i32())
+                    .map(|slab| SlabLocation::new(SlabIndex(slab), chunk_pos)),
+            );
         }
 
         drop(world);
+        self.dirty_chunks = dirty_chunks;
 
-        self.clean_slabs.extend(self.visible_slabs(range));
+        stats
     }
 
     fn invalidate_meshes(&mut self) {
@@ -213,6 +261,22 @@ impl<C: WorldContext> WorldViewer<C> {
         self.move_by(delta * size as i32);
     }
 
+    /// Moves the view range by the minimum amount to make entities at the given slice visible,
+    /// e.g. for a camera following an entity. Does nothing if already visible, so meshes are not
+    /// regenerated every time a followed entity moves
+    pub fn follow_slice(&mut self, slice: GlobalSliceIndex) {
+        let range = self.entity_range();
+        let delta = if slice > range.top() {
+            slice.slice() - range.top().slice()
+        } else if slice < range.bottom() {
+            slice.slice() - range.bottom().slice()
+        } else {
+            return;
+        };
+
+        self.update_range(self.view_range + delta, "followed");
+    }
+
     pub fn visible_chunks(&self) -> impl Iterator<Item = ChunkLocation> {
         let (min, max) = self.chunk_range;
         let xrange = min.0 - 1..=max.0;
@@ -327,3 +391,123 @@ impl AsRef<[SlabLocation]> for RequestedSlabs<'_> {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use color::Color;
+    use unit::world::{ChunkLocation, SlabIndex, SlabLocation, WorldPosition};
+
+    use crate::chunk::ChunkBuilder;
+    use crate::helpers::DummyBlockType;
+    use crate::world::helpers::world_from_chunks_blocking;
+    use crate::{BaseVertex, MeshRegenStats, SliceRange, WorldViewer};
+
+    #[derive(Copy, Clone, Debug)]
+    struct DummyVertex;
+
+    impl BaseVertex for DummyVertex {
+        fn new(_: (f32, f32, f32), _: Color) -> Self {
+            Self
+        }
+    }
+
+    #[test]
+    fn mesh_regen_budget() {
+        let chunks = (0..3)
+            .map(|x| {
+                ChunkBuilder::new()
+                    .fill_range((0, 0, 0), (3, 3, 0), |_| DummyBlockType::Stone)
+                    .build((x, 0))
+            })
+            .collect();
+        let world = world_from_chunks_blocking(chunks);
+
+        let mut viewer = WorldViewer::with_world(world, WorldPosition(0, 0, 1.into()), 4)
+            .expect("viewer should be created");
+        viewer.set_chunk_bounds((ChunkLocation(0, 0), ChunkLocation(2, 0)));
+
+        let mut regenerated = vec![];
+        let mut regen = |viewer: &mut WorldViewer<_>, budget| {
+            viewer
+                .regenerate_dirty_chunk_meshes_with_budget(budget, |chunk, _: Vec<DummyVertex>| {
+                    regenerated.push(chunk)
+                })
+        };
+
+        assert_eq!(
+            regen(&mut viewer, 2),
+            MeshRegenStats {
+                regenerated: 2,
+                pending: 1
+            }
+        );
+
+        assert_eq!(
+            regen(&mut viewer, 2),
+            MeshRegenStats {
+                regenerated: 1,
+                pending: 0
+            }
+        );
+
+        assert_eq!(regen(&mut viewer, 2), MeshRegenStats::default());
+
+        viewer.mark_dirty(SlabLocation::new(SlabIndex(0), ChunkLocation(2, 0)));
+        assert_eq!(
+            regen(&mut viewer, 2),
+            MeshRegenStats {
+                regenerated: 1,
+                pending: 0
+            }
+        );
+
+        // centre chunk first
+        assert_eq!(
+            regenerated,
+            vec![
+                ChunkLocation(1, 0),
+                ChunkLocation(0, 0),
+                ChunkLocation(2, 0),
+                ChunkLocation(2, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn follow_slice() {
+        let world = world_from_chunks_blocking(vec![ChunkBuilder::new()
+            .fill_range((0, 0, 0), (3, 3, 0), |_| DummyBlockType::Stone)
+            .build((0, 0))]);
+
+        let mut viewer = WorldViewer::with_world(world, WorldPosition(0, 0, 10.into()), 4)
+            .expect("viewer should be created");
+        let initial = SliceRange::from_bounds(8, 12).unwrap();
+        assert_eq!(viewer.terrain_range(), initial);
+
+        // already visible to entities
+        for slice in [9, 11, 13] {
+            viewer.follow_slice(slice.into());
+            assert_eq!(viewer.terrain_range(), initial);
+            assert!(viewer.requested_slabs.is_empty());
+        }
+
+        // above, moves up just enough
+        viewer.follow_slice(20.into());
+        assert_eq!(viewer.entity_range().top(), 20.into());
+        assert_eq!(
+            viewer.terrain_range(),
+            SliceRange::from_bounds(15, 19).unwrap()
+        );
+        assert!(!viewer.requested_slabs.is_empty());
+
+        // below, moves down just enough
+        viewer.requested_slabs.clear();
+        viewer.follow_slice(2.into());
+        assert_eq!(viewer.entity_range().bottom(), 2.into());
+        assert_eq!(
+            viewer.terrain_range(),
+            SliceRange::from_bounds(1, 5).unwrap()
+        );
+        assert!(!viewer.requested_slabs.is_empty());
+    }
+}
@@ -104,6 +104,32 @@ pub enum ExplorationResult {
     Abort,
 }
 
+/// Restricts the blocks a path can pass through, e.g. zones forbidden to an entity's society
+pub struct NavigationFilter(pub Box<dyn (Fn(WorldPosition) -> BlockAccess) + Send + Sync>);
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlockAccess {
+    Allowed,
+    /// Passable but more expensive, cost is multiplied by this (>= 1.0)
+    Discouraged(f32),
+    /// Never entered
+    Forbidden,
+}
+
+impl NavigationFilter {
+    /// None if forbidden
+    pub fn cost_multiplier(&self, pos: WorldPosition) -> Option<f32> {
+        match (self.0)(pos) {
+            BlockAccess::Allowed => Some(1.0),
+            BlockAccess::Discouraged(mul) => {
+                debug_assert!(mul >= 1.0, "bad cost multiplier {}", mul);
+                Some(mul)
+            }
+            BlockAccess::Forbidden => None,
+        }
+    }
+}
+
 impl<C: WorldContext> World<C> {
     pub fn empty() -> Self {
         Self {
@@ -160,6 +186,15 @@ impl<C: WorldContext> World<C> {
         &self,
         from: F,
         to: T,
+    ) -> Result<AreaPath, NavigationError> {
+        self.find_area_path_filtered(from, to, None)
+    }
+
+    fn find_area_path_filtered<F: Into<WorldPosition>, T: Into<WorldPosition>>(
+        &self,
+        from: F,
+        to: T,
+        filter: Option<&NavigationFilter>,
     ) -> Result<AreaPath, NavigationError> {
         // resolve areas
         let resolve_area = |pos: WorldPosition| {
@@ -175,9 +210,12 @@ impl<C: WorldContext> World<C> {
 
         let to_area = resolve_area(to).ok_or(NavigationError::TargetNotWalkable(to))?;
 
-        Ok(self
-            .area_graph
-            .find_area_path(from_area, to_area, &self.area_search_context)?)
+        Ok(self.area_graph.find_area_path_filtered(
+            from_area,
+            to_area,
+            filter,
+            &self.area_search_context,
+        )?)
     }
 
     fn find_block_path(
@@ -186,6 +224,7 @@ impl<C: WorldContext> World<C> {
         from: BlockPosition,
         to: BlockPosition,
         target: SearchGoal,
+        filter: Option<&NavigationFilter>,
     ) -> Result<BlockPath, NavigationError> {
         let block_graph = self
             .find_chunk_with_pos(area.chunk)
@@ -193,7 +232,13 @@ impl<C: WorldContext> World<C> {
             .ok_or(NavigationError::NoSuchArea(area))?;
 
         block_graph
-            .find_block_path(from, to, target, &self.block_search_context)
+            .find_block_path_filtered(
+                from,
+                to,
+                target,
+                filter.map(|f| (f, area.chunk)),
+                &self.block_search_context,
+            )
             .map_err(|e| NavigationError::BlockError(area, e))
     }
 
@@ -211,6 +256,29 @@ impl<C: WorldContext> World<C> {
         from: WorldPosition,
         to: WorldPosition,
         goal: SearchGoal,
+    ) -> Result<WorldPath, NavigationError> {
+        self.find_path_inner(from, to, goal, None)
+    }
+
+    /// Finds a path that respects the given per-entity access rules. Area transitions are chosen
+    /// before blocks within areas are checked, so a path can fail if the only way through an area
+    /// is forbidden, even if a detour through other areas exists
+    pub fn find_filtered_path(
+        &self,
+        from: WorldPosition,
+        to: WorldPosition,
+        goal: SearchGoal,
+        filter: &NavigationFilter,
+    ) -> Result<WorldPath, NavigationError> {
+        self.find_path_inner(from, to, goal, Some(filter))
+    }
+
+    fn find_path_inner(
+        &self,
+        from: WorldPosition,
+        to: WorldPosition,
+        goal: SearchGoal,
+        filter: Option<&NavigationFilter>,
     ) -> Result<WorldPath, NavigationError> {
         let from = self
             .find_accessible_block_in_column_with_range(from, None)
@@ -232,6 +300,9 @@ impl<C: WorldContext> World<C> {
                     .filter_map(|pos| {
                         self.find_accessible_block_in_column_with_range(pos, Some(pos.2 - 1))
                     })
+                    .filter(|pos| {
+                        filter.is_none_or(|filter| filter.cost_multiplier(*pos).is_some())
+                    })
                     .min_by_key(|pos| pos.distance2(from));
 
                 if let Some(neighbour) = accessible_neighbour {
@@ -251,7 +322,7 @@ impl<C: WorldContext> World<C> {
         }
 
         // find area path
-        let area_path = self.find_area_path(from, to)?;
+        let area_path = self.find_area_path_filtered(from, to, filter)?;
 
         // TODO optimize path with raytracing (#50)
         // TODO only calculate path for each area as needed (#51)
@@ -263,10 +334,21 @@ impl<C: WorldContext> World<C> {
         for (a, b) in area_path.0.iter().tuple_windows() {
             // unwrap ok because all except the first are Some
             let b_entry: AreaNavEdge = b.entry.unwrap();
-            let exit = b_entry.exit_closest(start);
+            let exit = match filter {
+                None => b_entry.exit_closest(start),
+                Some(filter) => b_entry
+                    .exit_closest_accessible(start, |exit, entry| {
+                        let exit = exit.to_world_position(a.area.chunk);
+                        let entry = entry.to_world_position(b.area.chunk);
+                        filter.cost_multiplier(exit).is_some()
+                            && filter.cost_multiplier(entry).is_some()
+                    })
+                    .ok_or(NavigationError::NoAccessibleExit(a.area))?,
+            };
 
             // block path from last point to exiting this area
-            let block_path = self.find_block_path(a.area, start, exit, SearchGoal::Arrive)?;
+            let block_path =
+                self.find_block_path(a.area, start, exit, SearchGoal::Arrive, filter)?;
             full_path.extend(Self::convert_block_path(a.area, block_path));
 
             // add transition edge from exit of this area to entering the next
@@ -276,15 +358,12 @@ impl<C: WorldContext> World<C> {
             });
 
             // continue from the entry point in the next chunk
-            start = {
-                let extended = b_entry.direction.extend_across_boundary_aligned(exit);
-                extended.above_by(b_entry.cost.z_offset())
-            };
+            start = b_entry.entry_for_exit(exit);
         }
 
         // final block path from entry of final area to goal
         let final_area = area_path.0.last().unwrap();
-        let block_path = self.find_block_path(final_area.area, start, to.into(), goal)?;
+        let block_path = self.find_block_path(final_area.area, start, to.into(), goal, filter)?;
         let real_target = block_path.target.to_world_position(final_area.area.chunk);
         full_path.extend(Self::convert_block_path(final_area.area, block_path));
 
@@ -651,6 +730,30 @@ impl<C: WorldContext> World<C> {
             .and_then(|chunk| chunk.get_block(pos.into()))
     }
 
+    /// Whether there is a solid block within `max_height` slices directly above the given
+    /// position, e.g. a roof or cave ceiling. This only checks the position's own column, so an
+    /// overhang or tree canopy counts too; it is not an enclosed space check. Only loaded slabs
+    /// are checked, so unloaded terrain above counts as open sky
+    pub fn has_roof_above(&self, pos: WorldPosition, max_height: u16) -> bool {
+        let chunk = match self.find_chunk_with_pos(ChunkLocation::from(pos)) {
+            Some(chunk) => chunk,
+            None => return false,
+        };
+
+        let (_, top_slab) = chunk.raw_terrain().slab_range();
+        let top = LocalSliceIndex::top()
+            .to_global(top_slab)
+            .slice()
+            .min(pos.2.slice() + max_height as i32);
+        ((pos.2.slice() + 1)..=top).any(|z| {
+            let above = WorldPosition(pos.0, pos.1, GlobalSliceIndex::new(z));
+            chunk
+                .get_block(above.into())
+                .map(|b| b.opacity().solid())
+                .unwrap_or_default()
+        })
+    }
+
     /// Mutates terrain silently to the loader, ensure the loader knows about this
     pub fn damage_block(
         &mut self,
@@ -1398,7 +1501,10 @@ mod tests {
         apply_updates, loader_from_chunks_blocking, world_from_chunks_blocking,
     };
     use crate::world::ContiguousChunkIterator;
-    use crate::{presets, BaseTerrain, BlockType, OcclusionChunkUpdate, SearchGoal, WorldContext};
+    use crate::{
+        presets, BaseTerrain, BlockAccess, BlockType, NavigationFilter, OcclusionChunkUpdate,
+        SearchGoal, WorldContext,
+    };
 
     #[test]
     fn world_context() {
@@ -1611,6 +1717,156 @@ mod tests {
         assert_eq!(path.path().len(), 3);
     }
 
+    #[test]
+    fn world_path_filtered() {
+        let world = world_from_chunks_blocking(vec![ChunkBuilder::new()
+            .fill_range((0, 0, 2), (8, 8, 2), |_| DummyBlockType::Stone)
+            .build((0, 0))])
+        .into_inner();
+
+        // wall of forbidden blocks with a gap at y=8
+        let filter = NavigationFilter(Box::new(|pos| {
+            if pos.0 == 4 && pos.1 < 8 {
+                BlockAccess::Forbidden
+            } else {
+                BlockAccess::Allowed
+            }
+        }));
+
+        let unfiltered = world
+            .find_path_with_goal((1, 1, 3).into(), (7, 1, 3).into(), SearchGoal::Arrive)
+            .expect("path should succeed");
+        assert!(unfiltered
+            .path()
+            .iter()
+            .any(|n| n.block.0 == 4 && n.block.1 < 8));
+
+        let path = world
+            .find_filtered_path(
+                (1, 1, 3).into(),
+                (7, 1, 3).into(),
+                SearchGoal::Arrive,
+                &filter,
+            )
+            .expect("path should go through gap");
+        assert!(path.path().len() > unfiltered.path().len());
+        assert!(path
+            .path()
+            .iter()
+            .filter(|n| n.block.0 == 4)
+            .all(|n| n.block.1 == 8));
+
+        // close the gap
+        let filter = NavigationFilter(Box::new(|pos| {
+            if pos.0 == 4 {
+                BlockAccess::Forbidden
+            } else {
+                BlockAccess::Allowed
+            }
+        }));
+
+        assert!(world
+            .find_filtered_path(
+                (1, 1, 3).into(),
+                (7, 1, 3).into(),
+                SearchGoal::Arrive,
+                &filter,
+            )
+            .is_err());
+
+        // closest neighbour of the target is forbidden, so arrive at another one
+        let filter = NavigationFilter(Box::new(|pos| {
+            if pos.0 == 6 && pos.1 == 1 {
+                BlockAccess::Forbidden
+            } else {
+                BlockAccess::Allowed
+            }
+        }));
+
+        let path = world
+            .find_filtered_path(
+                (1, 1, 3).into(),
+                (7, 1, 3).into(),
+                SearchGoal::Adjacent,
+                &filter,
+            )
+            .expect("path should arrive at an allowed neighbour");
+        let arrival = path.path().last().expect("path should not be empty").block;
+        assert_ne!((arrival.0, arrival.1), (6, 1));
+        assert!(arrival.0 >= 6 && arrival.1 <= 2);
+        assert!(path
+            .path()
+            .iter()
+            .all(|n| !(n.block.0 == 6 && n.block.1 == 1)));
+
+        // forbidden zone starting exactly at a chunk boundary, entered from the previous chunk
+        let world = world_from_chunks_blocking(vec![
+            ChunkBuilder::new()
+                .fill_range((0, 0, 2), (15, 15, 2), |_| DummyBlockType::Stone)
+                .build((0, 0)),
+            ChunkBuilder::new()
+                .fill_range((0, 0, 2), (15, 15, 2), |_| DummyBlockType::Stone)
+                .build((1, 0)),
+        ])
+        .into_inner();
+
+        let boundary_wall = |gap: Option<i32>| {
+            NavigationFilter(Box::new(move |pos| {
+                if pos.0 == 16 && Some(pos.1) != gap {
+                    BlockAccess::Forbidden
+                } else {
+                    BlockAccess::Allowed
+                }
+            }))
+        };
+
+        assert!(world
+            .find_filtered_path(
+                (1, 1, 3).into(),
+                (20, 1, 3).into(),
+                SearchGoal::Arrive,
+                &boundary_wall(None),
+            )
+            .is_err());
+
+        let path = world
+            .find_filtered_path(
+                (1, 1, 3).into(),
+                (20, 1, 3).into(),
+                SearchGoal::Arrive,
+                &boundary_wall(Some(10)),
+            )
+            .expect("path should go through gap");
+        assert!(path
+            .path()
+            .iter()
+            .filter(|n| n.block.0 == 16)
+            .all(|n| n.block.1 == 10));
+    }
+
+    #[test]
+    fn roof_above() {
+        let world = world_from_chunks_blocking(vec![ChunkBuilder::new()
+            .fill_range((0, 0, 0), (8, 8, 0), |_| DummyBlockType::Stone)
+            .fill_range((2, 2, 5), (4, 4, 5), |_| DummyBlockType::Grass)
+            .build((0, 0))])
+        .into_inner();
+
+        assert!(world.has_roof_above((3, 3, 1).into(), 8));
+        assert!(world.has_roof_above((2, 4, 4).into(), 8));
+        assert!(!world.has_roof_above((3, 3, 6).into(), 8));
+        assert!(!world.has_roof_above((6, 6, 1).into(), 8));
+        assert!(!world.has_roof_above((3, 3, 500).into(), 8));
+
+        // roof is too high
+        assert!(world.has_roof_above((3, 3, 1).into(), 4));
+        assert!(!world.has_roof_above((3, 3, 1).into(), 3));
+        assert!(!world.has_roof_above((3, 3, 4).into(), 0));
+
+        // no chunk
+        assert!(!world.has_roof_above((-5, 3, 1).into(), 8));
+    }
+
     #[test]
     fn find_chunk() {
         let world = world_from_chunks_blocking(vec![